@@ -0,0 +1,112 @@
+use raylib::prelude::*;
+
+use crate::car::Car;
+use crate::input::CarInput;
+use crate::track::Checkpoint;
+
+// Stay this far in from the ends of a gate, they sit on the road edge
+const GATE_MARGIN: f32 = 0.25;
+// Degrees off the target that still counts as full steering lock
+const FULL_LOCK_ANGLE: f32 = 25.0;
+// Never brake below this, so a car facing the wrong way can still turn
+const MIN_CORNER_SPEED: f32 = 12.0;
+
+// -------- Computer driver that follows the checkpoint gates --------
+#[derive(Copy, Clone)]
+pub struct AiDriver {
+    // 0.0 to 1.0, scales how close to the limit it drives
+    pub skill: f32,
+}
+
+impl AiDriver {
+    pub fn new(skill: f32) -> Self {
+        Self {
+            skill: skill.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn drive(&self, car: &Car, checkpoints: &[Checkpoint]) -> CarInput {
+        if checkpoints.is_empty() {
+            return CarInput::default();
+        }
+
+        let next = car.progress.next_checkpoint % checkpoints.len();
+        let gate = checkpoints[next];
+        let after = checkpoints[(next + 1) % checkpoints.len()].center();
+
+        let target = aim_point(&gate, car.position, after);
+        let forward = car.forward();
+        let angle = signed_angle(forward, target - car.position);
+
+        // Slow enough for the turn onto the target now, and early enough
+        // to make the corner at the coming gate
+        let turn_now = corner_speed(car, car.position.distance_to(target), angle.abs());
+
+        let approach = target - car.position;
+        let corner_at_gate = corner_speed(
+            car,
+            target.distance_to(after),
+            signed_angle(approach, after - target).abs(),
+        );
+        let braking =
+            (corner_at_gate.powi(2) + 2.0 * car.stats.brake_rate * approach.length()).sqrt();
+
+        // Better drivers carry more speed
+        let caution = 0.7 + 0.3 * self.skill;
+        let top_speed = car.stats.max_speed * (0.6 + 0.4 * self.skill);
+        let target_speed = (turn_now.min(braking) * caution).min(top_speed);
+
+        let acceleration = if car.speed < target_speed {
+            1.0
+        } else if car.speed > target_speed * 1.1 && car.speed > MIN_CORNER_SPEED {
+            -1.0
+        } else {
+            0.0
+        };
+
+        CarInput {
+            acceleration,
+            rot_vel: (angle.to_degrees() / FULL_LOCK_ANGLE).clamp(-1.0, 1.0),
+        }
+    }
+}
+
+// Point on the gate nearest the straight line on to the gate after it,
+// so the car cuts corners but still drives through every gate
+fn aim_point(gate: &Checkpoint, from: Vector2, next: Vector2) -> Vector2 {
+    let along = gate.end - gate.start;
+    let line = next - from;
+    let denom = along.x * line.y - along.y * line.x;
+
+    let t = if denom.abs() < f32::EPSILON {
+        0.5
+    } else {
+        let offset = from - gate.start;
+        (offset.x * line.y - offset.y * line.x) / denom
+    };
+
+    gate.start + along * t.clamp(GATE_MARGIN, 1.0 - GATE_MARGIN)
+}
+
+// Radians from `from` to `to`, positive turning the same way as steering right
+fn signed_angle(from: Vector2, to: Vector2) -> f32 {
+    (from.x * to.y - from.y * to.x).atan2(from.dot(to))
+}
+
+// Fastest speed at which the car can turn by `angle` within `distance`.
+// Turn rate grows with speed above 40% of top speed, below that it is fixed.
+fn corner_speed(car: &Car, distance: f32, angle: f32) -> f32 {
+    let half = (angle / 2.0).min(std::f32::consts::FRAC_PI_2);
+    if half.sin() < 0.01 {
+        return f32::INFINITY;
+    }
+
+    let radius = distance / (2.0 * half.sin());
+    let turn_rate = car.stats.handling.to_radians();
+
+    if radius * turn_rate >= car.stats.max_speed {
+        f32::INFINITY
+    } else {
+        (0.4 * radius * turn_rate).max(MIN_CORNER_SPEED)
+    }
+}
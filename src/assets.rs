@@ -0,0 +1,281 @@
+use raylib::prelude::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::rc::{Rc, Weak};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// Stand-in for textures that fail to load, big enough to see when scaled down
+const PLACEHOLDER_SIZE: i32 = 512;
+const PLACEHOLDER_CHECKS: i32 = 64;
+
+const PLACEHOLDER_ID: &str = "<placeholder>";
+
+const BANNER_DURATION: Duration = Duration::from_secs(8);
+const BANNER_MAX_LINES: usize = 3;
+
+#[derive(Debug)]
+pub enum AssetError {
+    NotFound(String),
+    Invalid { path: String, reason: String },
+    NoAudioDevice(String),
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::NotFound(path) => write!(f, "Missing file {}", path),
+            AssetError::Invalid { path, reason } => {
+                write!(f, "Could not load {}: {}", path, reason)
+            }
+            AssetError::NoAudioDevice(reason) => write!(f, "No audio device: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+fn check_exists(path: &str) -> Result<(), AssetError> {
+    if Path::new(path).is_file() {
+        Ok(())
+    } else {
+        Err(AssetError::NotFound(path.to_string()))
+    }
+}
+
+// raylib appends the path on a second line, which we already print
+fn invalid(path: &str, error: impl fmt::Display) -> AssetError {
+    let reason = error.to_string();
+
+    AssetError::Invalid {
+        path: path.to_string(),
+        reason: reason.lines().next().unwrap_or_default().to_string(),
+    }
+}
+
+// -------- Loaders returning typed errors --------
+pub fn load_image(path: &str) -> Result<Image, AssetError> {
+    check_exists(path)?;
+    Image::load_image(path).map_err(|e| invalid(path, e))
+}
+
+pub fn load_texture(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    path: &str,
+) -> Result<Texture2D, AssetError> {
+    check_exists(path)?;
+    rl.load_texture(thread, path).map_err(|e| invalid(path, e))
+}
+
+pub fn load_music<'aud>(audio: &'aud RaylibAudio, path: &str) -> Result<Music<'aud>, AssetError> {
+    check_exists(path)?;
+    audio.new_music(path).map_err(|e| invalid(path, e))
+}
+
+pub fn load_sound<'aud>(audio: &'aud RaylibAudio, path: &str) -> Result<Sound<'aud>, AssetError> {
+    check_exists(path)?;
+    audio.new_sound(path).map_err(|e| invalid(path, e))
+}
+
+// -------- Fallbacks that report the problem and carry on --------
+pub fn placeholder_image() -> Image {
+    Image::gen_image_checked(
+        PLACEHOLDER_SIZE,
+        PLACEHOLDER_SIZE,
+        PLACEHOLDER_CHECKS,
+        PLACEHOLDER_CHECKS,
+        Color::MAGENTA,
+        Color::BLACK,
+    )
+}
+
+pub fn image_or_placeholder(path: &str) -> Image {
+    load_image(path).unwrap_or_else(|error| {
+        report(&error);
+        placeholder_image()
+    })
+}
+
+pub fn placeholder_texture(rl: &mut RaylibHandle, thread: &RaylibThread) -> Texture2D {
+    // Only fails without a GL context, and then nothing could be drawn anyway
+    rl.load_texture_from_image(thread, &placeholder_image())
+        .expect("Failed to create placeholder texture")
+}
+
+// -------- Shared, reference-counted textures --------
+// Scenes hold `TextureHandle`s; the GPU texture is unloaded when the last
+// handle is dropped, and asking for the same path meanwhile reuses it.
+pub type TextureHandle = Rc<Texture2D>;
+
+#[derive(Copy, Clone, Default)]
+pub struct MemoryUsage {
+    pub textures: usize,
+    pub bytes: usize,
+}
+
+impl fmt::Display for MemoryUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} textures, {:.1} MB",
+            self.textures,
+            self.bytes as f32 / (1024.0 * 1024.0)
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct AssetCache {
+    textures: HashMap<String, Weak<Texture2D>>,
+}
+
+impl AssetCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn cached(&self, id: &str) -> Option<TextureHandle> {
+        self.textures.get(id).and_then(Weak::upgrade)
+    }
+
+    fn insert(&mut self, id: &str, texture: Texture2D) -> TextureHandle {
+        let handle = Rc::new(texture);
+        self.textures.insert(id.to_string(), Rc::downgrade(&handle));
+        println!("Loaded {} ({})", id, self.memory_usage());
+        handle
+    }
+
+    // Load by path, falling back to the shared placeholder on failure
+    pub fn texture(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        path: &str,
+    ) -> TextureHandle {
+        if let Some(handle) = self.cached(path) {
+            return handle;
+        }
+
+        match load_texture(rl, thread, path) {
+            Ok(texture) => self.insert(path, texture),
+            Err(error) => {
+                report(&error);
+                self.placeholder(rl, thread)
+            }
+        }
+    }
+
+    // Upload an image already in memory, shared under `id`
+    pub fn texture_from_image(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        id: &str,
+        image: &Image,
+    ) -> TextureHandle {
+        if let Some(handle) = self.cached(id) {
+            return handle;
+        }
+
+        match rl.load_texture_from_image(thread, image) {
+            Ok(texture) => self.insert(id, texture),
+            Err(error) => {
+                report(&invalid(id, error));
+                self.placeholder(rl, thread)
+            }
+        }
+    }
+
+    pub fn placeholder(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) -> TextureHandle {
+        if let Some(handle) = self.cached(PLACEHOLDER_ID) {
+            return handle;
+        }
+
+        let texture = placeholder_texture(rl, thread);
+        self.insert(PLACEHOLDER_ID, texture)
+    }
+
+    // Drop a stale entry so the next request loads the file again
+    pub fn invalidate(&mut self, id: &str) {
+        self.textures.remove(id);
+    }
+
+    // Forget textures nobody holds any more, they were unloaded on drop
+    pub fn prune(&mut self) {
+        let before = self.textures.len();
+        self.textures
+            .retain(|_, texture| texture.strong_count() > 0);
+
+        let freed = before - self.textures.len();
+        if freed > 0 {
+            println!("Freed {} textures ({})", freed, self.memory_usage());
+        }
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.textures.values().filter_map(Weak::upgrade).fold(
+            MemoryUsage::default(),
+            |usage, texture| MemoryUsage {
+                textures: usage.textures + 1,
+                bytes: usage.bytes + texture_size(&texture),
+            },
+        )
+    }
+}
+
+fn texture_size(texture: &Texture2D) -> usize {
+    let size = unsafe { ffi::GetPixelDataSize(texture.width, texture.height, texture.format) };
+    size.max(0) as usize
+}
+
+// -------- Error reporting: log plus an on-screen banner --------
+static RECENT_ERRORS: Mutex<Vec<(String, Instant)>> = Mutex::new(Vec::new());
+
+pub fn report(error: &AssetError) {
+    eprintln!("Asset error: {}", error);
+
+    if let Ok(mut errors) = RECENT_ERRORS.lock() {
+        errors.push((error.to_string(), Instant::now()));
+    }
+}
+
+// Drawn along the bottom edge, clear of the menus' Back buttons
+pub fn draw_error_banner(d: &mut RaylibDrawHandle, screen_width: i32, screen_height: i32) {
+    let Ok(mut errors) = RECENT_ERRORS.lock() else {
+        return;
+    };
+
+    errors.retain(|(_, time)| time.elapsed() < BANNER_DURATION);
+    if errors.is_empty() {
+        return;
+    }
+
+    let font_size = 20;
+    let line_height = font_size + 6;
+    let shown = errors.len().min(BANNER_MAX_LINES);
+    let hidden = errors.len() - shown;
+    let lines = shown + usize::from(hidden > 0);
+
+    let height = lines as i32 * line_height + 10;
+    let top = screen_height - height;
+
+    d.draw_rectangle(0, top, screen_width, height, Color::MAROON.alpha(0.85));
+
+    let mut y = top + 8;
+    for (message, _) in errors.iter().rev().take(shown) {
+        d.draw_text(message, 10, y, font_size, Color::WHITE);
+        y += line_height;
+    }
+
+    if hidden > 0 {
+        d.draw_text(
+            &format!("...and {} more, see the log", hidden),
+            10,
+            y,
+            font_size,
+            Color::WHITE,
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use raylib::prelude::*;
+
+use crate::assets::{load_music, load_sound, report};
+use crate::car::Car;
+use crate::game_data::MAX_PLAYERS;
+
+const ENGINE_PATH: &str = "Assets/sounds/engine.wav";
+const SQUEAL_PATH: &str = "Assets/sounds/squeal.wav";
+const RUMBLE_PATH: &str = "Assets/sounds/rumble.wav";
+const IMPACT_PATH: &str = "Assets/sounds/impact.wav";
+const CLICK_PATH: &str = "Assets/sounds/click.wav";
+
+// Engine pitch from standing still to flat out
+const IDLE_PITCH: f32 = 0.6;
+const TOP_PITCH: f32 = 2.0;
+// Share of top speed before hard steering starts to squeal
+const SQUEAL_SPEED: f32 = 0.5;
+// Sideways knock in pixels per second that squeals at full volume
+const FULL_SLIDE: f32 = 150.0;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum SoundCategory {
+    Music,
+    Engine,
+    Tires,
+    Impacts,
+    Ui,
+}
+
+impl SoundCategory {
+    pub const ALL: [SoundCategory; 5] = [
+        Self::Music,
+        Self::Engine,
+        Self::Tires,
+        Self::Impacts,
+        Self::Ui,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SoundCategory::Music => "Music",
+            SoundCategory::Engine => "Engine",
+            SoundCategory::Tires => "Tires",
+            SoundCategory::Impacts => "Impacts",
+            SoundCategory::Ui => "Menus",
+        }
+    }
+}
+
+// -------- Volume of each kind of sound, 0 to 1 --------
+// Every category is mixed under the master volume, which raylib applies
+// to everything it plays.
+#[derive(Copy, Clone, PartialEq)]
+pub struct Volumes {
+    pub master: f32,
+    pub muted: bool,
+    categories: [f32; 5],
+}
+
+impl Volumes {
+    // What raylib's master volume is set to
+    pub fn master_level(&self) -> f32 {
+        if self.muted { 0.0 } else { self.master }
+    }
+
+    pub fn get(&self, category: SoundCategory) -> f32 {
+        self.categories[category as usize]
+    }
+
+    pub fn set(&mut self, category: SoundCategory, volume: f32) {
+        self.categories[category as usize] = volume.clamp(0.0, 1.0);
+    }
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Self {
+            master: 0.5,
+            muted: false,
+            // Music, engine, tires, impacts, menus
+            categories: [0.4, 0.6, 0.7, 0.8, 0.6],
+        }
+    }
+}
+
+// One-off sounds that aren't tied to a car
+#[derive(Copy, Clone)]
+pub enum Effect {
+    Impact,
+    Click,
+}
+
+// -------- Every sound effect, loaded once at start up --------
+// Looping sounds are streams, which loop without a gap, and each car has
+// its own so their pitch can differ. Missing files are reported and
+// that sound stays silent.
+pub struct SoundEffects<'aud> {
+    engines: Vec<Music<'aud>>,
+    squeals: Vec<Music<'aud>>,
+    rumbles: Vec<Music<'aud>>,
+    impact: Option<Sound<'aud>>,
+    click: Option<Sound<'aud>>,
+}
+
+impl<'aud> SoundEffects<'aud> {
+    pub fn load(audio: &'aud RaylibAudio) -> Self {
+        let one_shot = |path: &str| load_sound(audio, path).map_err(|e| report(&e)).ok();
+
+        Self {
+            engines: load_voices(audio, ENGINE_PATH),
+            squeals: load_voices(audio, SQUEAL_PATH),
+            rumbles: load_voices(audio, RUMBLE_PATH),
+            impact: one_shot(IMPACT_PATH),
+            click: one_shot(CLICK_PATH),
+        }
+    }
+
+    pub fn play(&self, effect: Effect, volumes: &Volumes) {
+        let (sound, category) = match effect {
+            Effect::Impact => (&self.impact, SoundCategory::Impacts),
+            Effect::Click => (&self.click, SoundCategory::Ui),
+        };
+
+        if let Some(sound) = sound {
+            sound.set_volume(volumes.get(category));
+            sound.play();
+        }
+    }
+
+    // Engine, tire squeal and off-road rumble of the `index`th car, called
+    // every frame of a race so the streams keep playing
+    pub fn drive(&self, index: usize, car: &Car, volumes: &Volumes) {
+        let speed = (car.speed.abs() / car.stats.max_speed).clamp(0.0, 1.0);
+        let throttle = car.acceleration.clamp(0.0, 1.0);
+
+        if let Some(engine) = self.engines.get(index) {
+            let volume = 0.3 + 0.4 * speed + 0.3 * throttle;
+            engine.set_pitch(IDLE_PITCH + (TOP_PITCH - IDLE_PITCH) * speed);
+            keep_playing(engine, volumes.get(SoundCategory::Engine) * volume);
+        }
+
+        // Sliding after a hit, or turning hard at speed, squeals on the road
+        let steering = (car.rot_vel.abs() * speed - SQUEAL_SPEED) / (1.0 - SQUEAL_SPEED);
+        let slide = car.knock.length() / FULL_SLIDE;
+        let squeal = if car.surface.is_road() {
+            steering.max(slide).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        // Rolling over anything but road rumbles, louder the faster
+        let rumble = if car.surface.is_road() { 0.0 } else { speed };
+
+        let tires = volumes.get(SoundCategory::Tires);
+        if let Some(voice) = self.squeals.get(index) {
+            keep_playing(voice, tires * squeal);
+        }
+        if let Some(voice) = self.rumbles.get(index) {
+            keep_playing(voice, tires * rumble);
+        }
+    }
+
+    // Silence every car, when the race is left
+    pub fn stop_cars(&self) {
+        for voice in self
+            .engines
+            .iter()
+            .chain(&self.squeals)
+            .chain(&self.rumbles)
+        {
+            voice.stop_stream();
+        }
+    }
+}
+
+// A voice for each car that could be racing
+fn load_voices<'aud>(audio: &'aud RaylibAudio, path: &str) -> Vec<Music<'aud>> {
+    let mut voices = Vec::new();
+
+    for _ in 0..MAX_PLAYERS {
+        match load_music(audio, path) {
+            Ok(voice) => voices.push(voice),
+            Err(error) => {
+                report(&error);
+                break;
+            }
+        }
+    }
+
+    voices
+}
+
+fn keep_playing(voice: &Music, volume: f32) {
+    voice.set_volume(volume);
+    if !voice.is_stream_playing() {
+        voice.play_stream();
+    }
+    voice.update_stream();
+}
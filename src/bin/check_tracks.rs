@@ -0,0 +1,138 @@
+use racing_game::assets::load_image;
+use racing_game::car::{CAR_SCALE, CAR_WIDTH, car_sprite_paths};
+use racing_game::game_data::{CarChoice, TrackChoice};
+use racing_game::track::track_path;
+use racing_game::track_check::{Severity, check_track};
+use raylib::prelude::*;
+
+use std::process::exit;
+
+const USAGE: &str = "\
+Usage: check_tracks [options] [track.png ...]
+
+Checks track images before they ship, the bundled tracks by default.
+
+  --window <WxH>   window size the track is judged at (default 960x720)
+  --strict         fail on warnings as well as errors
+  --help           show this message
+
+Exits with 1 if any track has errors.";
+
+struct Options {
+    tracks: Vec<String>,
+    screen: (f32, f32),
+    strict: bool,
+    help: bool,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            tracks: Vec::new(),
+            screen: (960.0, 720.0),
+            strict: false,
+            help: false,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--window" => {
+                    let value = args.next().ok_or("--window needs a value")?;
+                    options.screen = parse_size(&value)?;
+                }
+                "--strict" => options.strict = true,
+                "--help" | "-h" => options.help = true,
+                _ if arg.starts_with("--") => return Err(format!("unknown option '{}'", arg)),
+                _ => options.tracks.push(arg),
+            }
+        }
+
+        if options.tracks.is_empty() {
+            options.tracks = TrackChoice::ALL.iter().map(track_path).collect();
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_size(value: &str) -> Result<(f32, f32), String> {
+    let size = value
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<f32>().ok()?, h.parse::<f32>().ok()?)));
+
+    match size {
+        Some((w, h)) if w > 0.0 && h > 0.0 => Ok((w, h)),
+        _ => Err(format!("expected a size like 1280x720, got '{}'", value)),
+    }
+}
+
+// Widest sprite on screen, never narrower than the collision shape
+fn widest_car() -> f32 {
+    CarChoice::ALL
+        .iter()
+        .flat_map(car_sprite_paths)
+        .filter_map(|path| load_image(&path).ok())
+        .map(|image| image.width as f32 * CAR_SCALE)
+        .fold(CAR_WIDTH, f32::max)
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            exit(2);
+        }
+    };
+
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    // raylib logs every image it loads, which buries the report
+    unsafe { ffi::SetTraceLogLevel(TraceLogLevel::LOG_WARNING as i32) };
+
+    let car_width = widest_car();
+    let (screen_w, screen_h) = options.screen;
+    let mut failed = false;
+
+    for path in &options.tracks {
+        let mut image = match load_image(path) {
+            Ok(image) => image,
+            Err(error) => {
+                println!("{}\n  error: {}", path, error);
+                failed = true;
+                continue;
+            }
+        };
+
+        let report = check_track(&mut image, screen_w, screen_h, car_width);
+
+        println!(
+            "{} ({}x{}, {:.0}% cropped, narrowest road {:.0}px)",
+            path,
+            image.width,
+            image.height,
+            report.crop * 100.0,
+            report.min_width
+        );
+        for issue in &report.issues {
+            println!("  {}", issue);
+        }
+        if report.issues.is_empty() {
+            println!("  ok");
+        }
+
+        let warned = report
+            .issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Warning);
+        failed |= report.has_errors() || (options.strict && warned);
+    }
+
+    if failed {
+        exit(1);
+    }
+}
@@ -0,0 +1,556 @@
+use racing_game::ai::AiDriver;
+use racing_game::assets::load_image;
+use racing_game::car::{CAR_DATA_PATH, Car, CarTable};
+use racing_game::display::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use racing_game::game_data::{CarChoice, TrackChoice};
+use racing_game::input::CarInput;
+use racing_game::race::{grid_position, offset_spawn_for_car, step_race};
+use racing_game::track::{layout_track, road_at, surface_at, track_path};
+use raylib::prelude::*;
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::process::exit;
+
+const USAGE: &str = "\
+Usage: simulate [options]
+
+Races cars round tracks without a window and prints the results as JSON.
+
+  --track <list>           track numbers or image paths, comma separated, or all (default)
+  --car <list>             car numbers, comma separated, or all (default)
+  --laps <n>               number of laps (default 3)
+  --solo                   race each car on its own instead of all together
+  --skill <0-1>            AI driver skill (default 1)
+  --script <file>          drive the first car from a script instead of the AI
+  --car-data <file>        car stats table (default Assets/cars.txt)
+  --max-time <seconds>     give up on a car after this long (default 300)
+  --baseline <file>        exit with 1 if results regress against this file
+  --write-baseline <file>  store these results as the new baseline
+  --tolerance <seconds>    race time allowed over the baseline (default 0.5)
+  --help                   show this message
+
+Script lines are `time acceleration steering`, each held until the next
+line, e.g. `0.0 1 0` then `2.5 1 -0.5`. Lines starting with # are ignored.
+
+The stored baseline was made with the defaults and --solo, so check with
+  cargo run --release --bin simulate -- --solo --baseline baseline.tsv";
+
+// Same step as a 60 fps frame, so results match what players see
+const DT: f32 = 1.0 / 60.0;
+// Tracks are laid out at the size races are drawn at
+const SCREEN_WIDTH: f32 = VIRTUAL_WIDTH as f32;
+const SCREEN_HEIGHT: f32 = VIRTUAL_HEIGHT as f32;
+
+// -------- Command line --------
+struct Options {
+    tracks: Vec<String>,
+    cars: Vec<CarChoice>,
+    laps: u32,
+    solo: bool,
+    skill: f32,
+    script: Option<String>,
+    car_data: String,
+    max_time: f32,
+    baseline: Option<String>,
+    write_baseline: Option<String>,
+    tolerance: f32,
+    help: bool,
+}
+
+impl Options {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            tracks: TrackChoice::ALL.iter().map(track_path).collect(),
+            cars: CarChoice::ALL.to_vec(),
+            laps: 3,
+            solo: false,
+            skill: 1.0,
+            script: None,
+            car_data: CAR_DATA_PATH.to_string(),
+            max_time: 300.0,
+            baseline: None,
+            write_baseline: None,
+            tolerance: 0.5,
+            help: false,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+
+            match arg.as_str() {
+                "--track" => options.tracks = parse_tracks(&value()?)?,
+                "--car" => options.cars = parse_cars(&value()?)?,
+                "--laps" => options.laps = parse_number(&value()?, 1.0, f32::MAX)? as u32,
+                "--solo" => options.solo = true,
+                "--skill" => options.skill = parse_number(&value()?, 0.0, 1.0)?,
+                "--script" => options.script = Some(value()?),
+                "--car-data" => options.car_data = value()?,
+                "--max-time" => options.max_time = parse_number(&value()?, 1.0, f32::MAX)?,
+                "--baseline" => options.baseline = Some(value()?),
+                "--write-baseline" => options.write_baseline = Some(value()?),
+                "--tolerance" => options.tolerance = parse_number(&value()?, 0.0, f32::MAX)?,
+                "--help" | "-h" => options.help = true,
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+// Numbers pick the bundled tracks, anything else is an image path
+fn parse_tracks(value: &str) -> Result<Vec<String>, String> {
+    if value == "all" {
+        return Ok(TrackChoice::ALL.iter().map(track_path).collect());
+    }
+
+    value
+        .split(',')
+        .map(|item| match item.parse::<usize>() {
+            Ok(n) => n
+                .checked_sub(1)
+                .and_then(|i| TrackChoice::ALL.get(i))
+                .map(track_path)
+                .ok_or(format!("expected a track from 1 to 4, got '{}'", item)),
+            Err(_) => Ok(item.to_string()),
+        })
+        .collect()
+}
+
+fn parse_cars(value: &str) -> Result<Vec<CarChoice>, String> {
+    if value == "all" {
+        return Ok(CarChoice::ALL.to_vec());
+    }
+
+    value
+        .split(',')
+        .map(|item| {
+            item.parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| CarChoice::ALL.get(i).copied())
+                .ok_or(format!("expected a car from 1 to 4, got '{}'", item))
+        })
+        .collect()
+}
+
+fn parse_number(value: &str, min: f32, max: f32) -> Result<f32, String> {
+    value
+        .parse::<f32>()
+        .ok()
+        .filter(|n| (min..=max).contains(n))
+        .ok_or(format!(
+            "expected a number from {} to {}, got '{}'",
+            min, max, value
+        ))
+}
+
+// -------- Scripted inputs --------
+struct Script {
+    steps: Vec<(f32, CarInput)>,
+}
+
+impl Script {
+    fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut steps = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let values: Vec<f32> = line
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|_| format!("{} line {}: expected numbers", path, number + 1))?;
+
+            let [time, acceleration, rot_vel] = values[..] else {
+                return Err(format!(
+                    "{} line {}: expected `time acceleration steering`",
+                    path,
+                    number + 1
+                ));
+            };
+
+            steps.push((
+                time,
+                CarInput {
+                    acceleration: acceleration.clamp(-1.0, 1.0),
+                    rot_vel: rot_vel.clamp(-1.0, 1.0),
+                },
+            ));
+        }
+
+        steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(Self { steps })
+    }
+
+    fn input_at(&self, time: f32) -> CarInput {
+        self.steps
+            .iter()
+            .take_while(|(start, _)| *start <= time)
+            .last()
+            .map(|(_, input)| *input)
+            .unwrap_or_default()
+    }
+}
+
+// -------- One car's race --------
+struct CarResult {
+    track: String,
+    car: usize,
+    driver: &'static str,
+    // Cars in the race, results alone and in traffic differ
+    field: usize,
+    finished: bool,
+    race_time: f32,
+    lap_times: Vec<f32>,
+    best_lap: Option<f32>,
+    off_track: u32,
+    off_track_time: f32,
+    collisions: u32,
+}
+
+impl CarResult {
+    fn key(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.track, self.car, self.driver, self.field
+        )
+    }
+
+    fn to_json(&self) -> String {
+        let laps: Vec<String> = self.lap_times.iter().map(|t| format!("{:.3}", t)).collect();
+        let best = self
+            .best_lap
+            .map_or("null".to_string(), |t| format!("{:.3}", t));
+
+        format!(
+            "{{\"track\": \"{}\", \"car\": {}, \"driver\": \"{}\", \"field\": {}, \"finished\": {}, \
+             \"race_time\": {:.3}, \"lap_times\": [{}], \"best_lap\": {}, \
+             \"off_track\": {}, \"off_track_time\": {:.3}, \"collisions\": {}}}",
+            self.track.replace('\\', "\\\\").replace('"', "\\\""),
+            self.car,
+            self.driver,
+            self.field,
+            self.finished,
+            self.race_time,
+            laps.join(", "),
+            best,
+            self.off_track,
+            self.off_track_time,
+            self.collisions
+        )
+    }
+}
+
+// Runs one race to the end, or until `max_time` for any car still going
+fn simulate(
+    track: &str,
+    image: &mut Image,
+    choices: &[CarChoice],
+    car_table: &CarTable,
+    script: Option<&Script>,
+    options: &Options,
+) -> Vec<CarResult> {
+    let layout = layout_track(image, SCREEN_WIDTH, SCREEN_HEIGHT);
+    let ai = AiDriver::new(options.skill);
+
+    let mut cars: Vec<Car> = choices
+        .iter()
+        .enumerate()
+        .map(|(i, &choice)| {
+            let pos = grid_position(layout.spawn, layout.direction, i, choices.len());
+            let mut car = Car::new(
+                choice,
+                None,
+                offset_spawn_for_car(pos, layout.direction),
+                layout.direction,
+            );
+            car.stats = car_table.stats(&choice);
+            car
+        })
+        .collect();
+
+    let mut results: Vec<CarResult> = cars
+        .iter()
+        .enumerate()
+        .map(|(i, car)| CarResult {
+            track: track.to_string(),
+            car: CarChoice::ALL
+                .iter()
+                .position(|&c| c == car.choice)
+                .unwrap_or(0)
+                + 1,
+            driver: if i == 0 && script.is_some() {
+                "script"
+            } else {
+                "ai"
+            },
+            field: choices.len(),
+            finished: false,
+            race_time: 0.0,
+            lap_times: Vec::new(),
+            best_lap: None,
+            off_track: 0,
+            off_track_time: 0.0,
+            collisions: 0,
+        })
+        .collect();
+
+    let mut on_road: Vec<bool> = vec![true; cars.len()];
+    let mut time = 0.0;
+
+    while time < options.max_time && !cars.iter().all(|car| car.progress.finished) {
+        for (i, car) in cars.iter_mut().enumerate() {
+            let input = match script {
+                Some(script) if i == 0 => script.input_at(time),
+                _ => ai.drive(car, &layout.checkpoints),
+            };
+            car.apply_input(input);
+        }
+
+        let laps_before: Vec<u32> = cars.iter().map(completed_laps).collect();
+        let hits_before: Vec<u32> = cars.iter().map(|car| car.collisions).collect();
+
+        step_race(
+            &mut cars,
+            &layout.checkpoints,
+            |point| surface_at(image, SCREEN_WIDTH, SCREEN_HEIGHT, point),
+            DT,
+            options.laps,
+            false,
+        );
+        time += DT;
+
+        for (i, car) in cars.iter().enumerate() {
+            let result = &mut results[i];
+
+            if completed_laps(car) > laps_before[i]
+                && let Some(lap) = car.progress.last_lap
+            {
+                result.lap_times.push(lap);
+            }
+            result.collisions += car.collisions - hits_before[i];
+
+            if car.progress.finished {
+                continue;
+            }
+
+            // An excursion counts once, however long the car stays off
+            let road = road_at(image, SCREEN_WIDTH, SCREEN_HEIGHT, car.position);
+            if !road {
+                result.off_track_time += DT;
+                if on_road[i] {
+                    result.off_track += 1;
+                }
+            }
+            on_road[i] = road;
+        }
+    }
+
+    for (result, car) in results.iter_mut().zip(&cars) {
+        result.finished = car.progress.finished;
+        result.race_time = car.race_time;
+        result.best_lap = car.progress.best_lap;
+    }
+
+    results
+}
+
+fn completed_laps(car: &Car) -> u32 {
+    car.progress.lap - 1 + u32::from(car.progress.finished)
+}
+
+// -------- Baseline: one tab separated line per car --------
+// track, car, driver, field, finished, race_time, off_track, collisions
+fn write_baseline(path: &str, results: &[CarResult]) -> Result<(), String> {
+    let mut text =
+        String::from("# track\tcar\tdriver\tfield\tfinished\trace_time\toff_track\tcollisions\n");
+    for result in results {
+        let _ = writeln!(
+            text,
+            "{}\t{}\t{:.3}\t{}\t{}",
+            result.key(),
+            result.finished,
+            result.race_time,
+            result.off_track,
+            result.collisions
+        );
+    }
+
+    fs::write(path, text).map_err(|e| format!("{}: {}", path, e))
+}
+
+struct BaselineEntry {
+    finished: bool,
+    race_time: f32,
+    off_track: u32,
+    collisions: u32,
+}
+
+fn read_baseline(path: &str) -> Result<HashMap<String, BaselineEntry>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut entries = HashMap::new();
+
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (key, entry) =
+            parse_baseline_line(line).ok_or(format!("{} line {}: malformed", path, number + 1))?;
+        entries.insert(key, entry);
+    }
+
+    Ok(entries)
+}
+
+// The first four fields identify the run, matching `CarResult::key`
+fn parse_baseline_line(line: &str) -> Option<(String, BaselineEntry)> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    let [
+        track,
+        car,
+        driver,
+        field,
+        finished,
+        race_time,
+        off_track,
+        collisions,
+    ] = fields[..]
+    else {
+        return None;
+    };
+
+    let entry = BaselineEntry {
+        finished: finished.parse().ok()?,
+        race_time: race_time.parse().ok()?,
+        off_track: off_track.parse().ok()?,
+        collisions: collisions.parse().ok()?,
+    };
+
+    Some((format!("{}\t{}\t{}\t{}", track, car, driver, field), entry))
+}
+
+// Describes every way the results got worse than the baseline
+fn regressions(
+    results: &[CarResult],
+    baseline: &HashMap<String, BaselineEntry>,
+    tolerance: f32,
+) -> Vec<String> {
+    let mut found = Vec::new();
+
+    for result in results {
+        let name = format!(
+            "{} car {} ({}, {} in the race)",
+            result.track, result.car, result.driver, result.field
+        );
+        let Some(base) = baseline.get(&result.key()) else {
+            eprintln!("No baseline for {}, skipped", name);
+            continue;
+        };
+
+        if base.finished && !result.finished {
+            found.push(format!("{}: no longer finishes", name));
+        } else if result.finished && result.race_time > base.race_time + tolerance {
+            found.push(format!(
+                "{}: race time {:.3}s, baseline {:.3}s",
+                name, result.race_time, base.race_time
+            ));
+        }
+
+        if result.off_track > base.off_track {
+            found.push(format!(
+                "{}: {} off-track excursions, baseline {}",
+                name, result.off_track, base.off_track
+            ));
+        }
+
+        if result.collisions > base.collisions {
+            found.push(format!(
+                "{}: {} collisions, baseline {}",
+                name, result.collisions, base.collisions
+            ));
+        }
+    }
+
+    found
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    exit(2);
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => fail(&format!("{}\n\n{}", error, USAGE)),
+    };
+
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    // raylib logs image loads to stdout, which would break the JSON
+    unsafe { ffi::SetTraceLogLevel(TraceLogLevel::LOG_WARNING as i32) };
+
+    let car_table = CarTable::load(&options.car_data).unwrap_or_else(|e| fail(&e.to_string()));
+    let script = options
+        .script
+        .as_deref()
+        .map(|path| Script::load(path).unwrap_or_else(|e| fail(&e)));
+
+    // Solo runs race each car alone, otherwise they share the grid
+    let fields: Vec<Vec<CarChoice>> = if options.solo {
+        options.cars.iter().map(|&car| vec![car]).collect()
+    } else {
+        vec![options.cars.clone()]
+    };
+
+    let mut results = Vec::new();
+    for track in &options.tracks {
+        let mut image = load_image(track).unwrap_or_else(|e| fail(&e.to_string()));
+
+        for field in &fields {
+            results.extend(simulate(
+                track,
+                &mut image,
+                field,
+                &car_table,
+                script.as_ref(),
+                &options,
+            ));
+        }
+    }
+
+    let lines: Vec<String> = results
+        .iter()
+        .map(|result| format!("  {}", result.to_json()))
+        .collect();
+    println!("[\n{}\n]", lines.join(",\n"));
+
+    if let Some(path) = &options.write_baseline {
+        write_baseline(path, &results).unwrap_or_else(|e| fail(&e));
+    }
+
+    if let Some(path) = &options.baseline {
+        let baseline = read_baseline(path).unwrap_or_else(|e| fail(&e));
+        let found = regressions(&results, &baseline, options.tolerance);
+
+        if !found.is_empty() {
+            for regression in &found {
+                eprintln!("Regression: {}", regression);
+            }
+            exit(1);
+        }
+    }
+}
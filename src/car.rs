@@ -0,0 +1,405 @@
+use raylib::prelude::*;
+
+use crate::assets::{AssetCache, AssetError, TextureHandle};
+use crate::game_data::{CarChoice, ControlChoice};
+use crate::input::CarInput;
+use crate::track::{LapProgress, Surface};
+
+pub const CAR_SCALE: f32 = 0.05;
+pub const CAR_SPRITE_ROT_OFFSET: f32 = -90.0; // sprite artwork faces up
+
+// Footprint of the scaled sprites, used for contact between cars
+pub const CAR_LENGTH: f32 = 50.0;
+pub const CAR_WIDTH: f32 = 22.0;
+// A car is about 4.5m long, for showing speeds and distances
+pub const METERS_PER_PIXEL: f32 = 4.5 / CAR_LENGTH;
+
+// Tuning overrides for the built-in stats below, reloadable in dev mode
+pub const CAR_DATA_PATH: &str = "Assets/cars.txt";
+
+// How quickly sideways shoves and spins from hits die away
+const KNOCK_DAMPING: f32 = 4.0;
+const SPIN_DAMPING: f32 = 3.0;
+
+pub struct CarSprites {
+    pub straight: TextureHandle,
+    pub left: TextureHandle,
+    pub right: TextureHandle,
+}
+
+impl CarSprites {
+    // Players on the same car share one set of textures
+    pub fn load(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        assets: &mut AssetCache,
+        car: &CarChoice,
+    ) -> Self {
+        let [straight, left, right] =
+            car_sprite_paths(car).map(|path| assets.texture(rl, thread, &path));
+
+        Self {
+            straight,
+            left,
+            right,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct CarStats {
+    pub accel_rate: f32,
+    pub brake_rate: f32,
+    pub drag: f32,
+    pub max_speed: f32,
+    pub handling: f32,
+    pub mass: f32,
+}
+
+pub fn car_stats(car: &CarChoice) -> CarStats {
+    let (accel_rate, brake_rate, drag, max_speed, handling, mass) = match car {
+        CarChoice::Car1 => (200.0, 300.0, 4.0, 400.0, 120.0, 1.0),
+        CarChoice::Car2 => (100.0, 100.0, 1.0, 1000.0, 240.0, 1.6),
+        CarChoice::Car3 => (500.0, 500.0, 8.0, 200.0, 300.0, 0.8),
+        CarChoice::Car4 => (600.0, 200.0, 4.0, 400.0, 60.0, 1.3),
+    };
+
+    CarStats {
+        accel_rate,
+        brake_rate,
+        drag,
+        max_speed,
+        handling,
+        mass,
+    }
+}
+
+// -------- Stats for every car, read from CAR_DATA_PATH --------
+// One line per car: name accel brake drag max_speed handling mass
+#[derive(Copy, Clone)]
+pub struct CarTable {
+    stats: [CarStats; 4],
+}
+
+impl CarTable {
+    pub fn load(path: &str) -> Result<Self, AssetError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|_| AssetError::NotFound(path.to_string()))?;
+        let invalid = |line: usize, reason: &str| AssetError::Invalid {
+            path: path.to_string(),
+            reason: format!("line {}: {}", line + 1, reason),
+        };
+
+        let mut table = Self::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let name = fields.next().unwrap_or_default();
+            let Some(index) = CarChoice::ALL.iter().position(|car| car_key(car) == name) else {
+                return Err(invalid(i, &format!("unknown car '{}'", name)));
+            };
+
+            let values = fields
+                .map(|field| field.parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| invalid(i, "expected numbers"))?;
+
+            let [accel_rate, brake_rate, drag, max_speed, handling, mass] = values[..] else {
+                return Err(invalid(i, "expected 6 values"));
+            };
+
+            if mass <= 0.0 || max_speed <= 0.0 {
+                return Err(invalid(i, "mass and max_speed must be positive"));
+            }
+
+            table.stats[index] = CarStats {
+                accel_rate,
+                brake_rate,
+                drag,
+                max_speed,
+                handling,
+                mass,
+            };
+        }
+
+        Ok(table)
+    }
+
+    pub fn stats(&self, car: &CarChoice) -> CarStats {
+        let index = CarChoice::ALL.iter().position(|c| c == car).unwrap_or(0);
+        self.stats[index]
+    }
+}
+
+impl Default for CarTable {
+    fn default() -> Self {
+        Self {
+            stats: CarChoice::ALL.map(|car| car_stats(&car)),
+        }
+    }
+}
+
+fn car_key(car: &CarChoice) -> &'static str {
+    match car {
+        CarChoice::Car1 => "car1",
+        CarChoice::Car2 => "car2",
+        CarChoice::Car3 => "car3",
+        CarChoice::Car4 => "car4",
+    }
+}
+
+pub fn car_name(car: &CarChoice) -> &'static str {
+    match car {
+        CarChoice::Car1 => "Default car",
+        CarChoice::Car2 => "High inertia car",
+        CarChoice::Car3 => "Responsive car",
+        CarChoice::Car4 => "Stubborn car",
+    }
+}
+
+// -------- Classes that time trial targets are set for --------
+// Grouped by top speed, A the fastest
+#[derive(Copy, Clone, PartialEq)]
+pub enum CarClass {
+    A,
+    B,
+    C,
+}
+
+impl CarClass {
+    pub const ALL: [CarClass; 3] = [Self::A, Self::B, Self::C];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::A => "A",
+            Self::B => "B",
+            Self::C => "C",
+        }
+    }
+}
+
+pub fn car_class(car: &CarChoice) -> CarClass {
+    match car {
+        CarChoice::Car2 => CarClass::A,
+        CarChoice::Car1 | CarChoice::Car4 => CarClass::B,
+        CarChoice::Car3 => CarClass::C,
+    }
+}
+
+// Straight, left and right frames
+pub fn car_sprite_paths(car: &CarChoice) -> [String; 3] {
+    let (folder, base) = car_sprite_folder(car);
+
+    ["", "_left", "_right"].map(|suffix| format!("{}/{}{}.png", folder, base, suffix))
+}
+
+fn car_sprite_folder(car: &CarChoice) -> (&'static str, &'static str) {
+    match car {
+        CarChoice::Car1 => ("Assets/frames/car1", "1"),
+        CarChoice::Car2 => ("Assets/frames/car2", "2"),
+        CarChoice::Car3 => ("Assets/frames/car3", "3"),
+        CarChoice::Car4 => ("Assets/frames/car4", "4"),
+    }
+}
+
+pub struct Car {
+    pub choice: CarChoice,
+    pub control: Option<ControlChoice>,
+    pub stats: CarStats,
+
+    pub position: Vector2,
+    pub direction: f32,
+    // Degrees the car is actually moving in, which lags `direction` in a
+    // slide. Only loosened by `drift_grip`.
+    pub travel: f32,
+    // How quickly the travel swings round to the nose, per second. None
+    // grips completely, so the car goes where it points.
+    pub drift_grip: Option<f32>,
+    pub speed: f32,
+    pub acceleration: f32,
+    pub rot_vel: f32,
+    // Ground under the car, set before each update
+    pub surface: Surface,
+
+    // Velocity and spin picked up from hits, on top of the driven motion
+    pub knock: Vector2,
+    pub spin: f32,
+    pub collisions: u32,
+
+    pub race_time: f32,
+    pub race_started: bool,
+    pub progress: LapProgress,
+    // Knocked out of the race, left where it stopped and no longer moved,
+    // hit or timed
+    pub retired: bool,
+
+    pub sprites: Option<CarSprites>,
+}
+
+impl Car {
+    pub fn new(
+        choice: CarChoice,
+        control: Option<ControlChoice>,
+        position: Vector2,
+        direction: f32,
+    ) -> Self {
+        Self {
+            choice,
+            control,
+            stats: car_stats(&choice),
+            position,
+            direction,
+            travel: direction,
+            drift_grip: None,
+            speed: 0.0,
+            acceleration: 0.0,
+            rot_vel: 0.0,
+            surface: Surface::Road,
+            knock: Vector2::zero(),
+            spin: 0.0,
+            collisions: 0,
+            race_time: 0.0,
+            race_started: false,
+            progress: LapProgress::new(),
+            retired: false,
+            sprites: None,
+        }
+    }
+
+    pub fn forward(&self) -> Vector2 {
+        let rad = self.direction.to_radians();
+        Vector2::new(rad.cos(), rad.sin())
+    }
+
+    fn travel_vector(&self) -> Vector2 {
+        let rad = self.travel.to_radians();
+        Vector2::new(rad.cos(), rad.sin())
+    }
+
+    pub fn velocity(&self) -> Vector2 {
+        self.travel_vector() * self.speed + self.knock
+    }
+
+    // Degrees between where the car points and where it's going, 0 to 180
+    pub fn slip_angle(&self) -> f32 {
+        angle_between(self.travel, self.direction).abs()
+    }
+
+    // Out of the race, stopped where it is
+    pub fn retire(&mut self) {
+        self.retired = true;
+        self.speed = 0.0;
+        self.acceleration = 0.0;
+        self.knock = Vector2::zero();
+        self.spin = 0.0;
+    }
+
+    // Split a change in velocity into driven speed and sideways knock
+    pub fn add_velocity(&mut self, delta: Vector2) {
+        let along_travel = self.travel_vector();
+        let along = delta.dot(along_travel);
+
+        self.speed += along;
+        self.knock += delta - along_travel * along;
+    }
+
+    pub fn apply_input(&mut self, input: CarInput) {
+        if self.progress.finished {
+            self.acceleration = 0.0;
+            self.rot_vel = 0.0;
+        } else {
+            self.acceleration = input.acceleration;
+            self.rot_vel = input.rot_vel;
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if !self.race_started && self.speed.abs() > 1.0 {
+            self.race_started = true;
+        }
+
+        if self.race_started && !self.progress.finished {
+            self.race_time += dt;
+        }
+
+        let stats = self.stats;
+
+        let accel = if self.acceleration > 0.0 {
+            stats.accel_rate
+        } else if self.acceleration < 0.0 {
+            -stats.brake_rate
+        } else {
+            0.0
+        };
+
+        self.speed += accel * dt;
+        if accel == 0.0 {
+            self.speed -= self.speed * stats.drag * dt;
+        }
+        self.speed -= self.speed * self.surface.drag() * dt;
+
+        self.speed = self.speed.clamp(-0.5 * stats.max_speed, stats.max_speed);
+
+        let steering = self.rot_vel * stats.handling * self.surface.grip();
+        let speed_factor = (self.speed.abs() / stats.max_speed).clamp(0.4, 1.0);
+
+        self.direction = (self.direction + (steering * speed_factor + self.spin) * dt) % 360.0;
+
+        // The momentum follows the nose round, slower on slippery ground
+        self.travel = match self.drift_grip {
+            None => self.direction,
+            Some(grip) => {
+                let catch_up = (grip * self.surface.grip() * dt).min(1.0);
+                self.travel + angle_between(self.travel, self.direction) * catch_up
+            }
+        };
+
+        self.position += self.velocity() * dt;
+
+        self.knock *= (1.0 - KNOCK_DAMPING * dt).max(0.0);
+        self.spin *= (1.0 - SPIN_DAMPING * dt).max(0.0);
+    }
+
+    pub fn draw<D: RaylibDraw>(&self, d: &mut D, tint: Color) {
+        let Some(sprites) = &self.sprites else {
+            return;
+        };
+
+        let tex: &Texture2D = if self.rot_vel > 0.2 {
+            &sprites.right
+        } else if self.rot_vel < -0.2 {
+            &sprites.left
+        } else {
+            &sprites.straight
+        };
+
+        let dest = Rectangle {
+            x: self.position.x,
+            y: self.position.y,
+            width: tex.width as f32 * CAR_SCALE,
+            height: tex.height as f32 * CAR_SCALE,
+        };
+
+        d.draw_texture_pro(
+            tex,
+            Rectangle::new(0.0, 0.0, tex.width as f32, tex.height as f32),
+            dest,
+            Vector2 {
+                x: dest.width / 2.0,
+                y: dest.height / 2.0,
+            },
+            self.direction + CAR_SPRITE_ROT_OFFSET,
+            tint,
+        );
+    }
+}
+
+// Signed degrees to turn from `from` to face `to`, -180 to 180
+fn angle_between(from: f32, to: f32) -> f32 {
+    (to - from + 540.0).rem_euclid(360.0) - 180.0
+}
@@ -0,0 +1,353 @@
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::assets::{AssetError, report};
+use crate::game_data::{CarChoice, Difficulty, GameData, MAX_CARS, TrackChoice};
+
+pub const CHAMPIONSHIP_PATH: &str = "Assets/championship.txt";
+
+// Awarded by finishing place, the rest score nothing
+pub const POINTS: [u32; MAX_CARS] = [10, 8, 6, 5, 4, 3];
+
+// One driver in the cup, the players first then the computer cars in the
+// order GameScene lines them up
+#[derive(Clone)]
+pub struct Entrant {
+    pub car: CarChoice,
+    pub human: bool,
+    pub points: u32,
+    // Place in the last race run, None before the first
+    pub last_place: Option<usize>,
+}
+
+// -------- A cup of races against the same field --------
+// Saved after every race so it can be picked up again after quitting
+#[derive(Clone)]
+pub struct Championship {
+    pub tracks: Vec<TrackChoice>,
+    // Races run so far, and so the index of the next one
+    pub races_run: usize,
+    pub laps: u32,
+    pub difficulty: Difficulty,
+    pub entrants: Vec<Entrant>,
+}
+
+impl Championship {
+    // Round the bundled tracks with the players and opponents picked
+    pub fn new(data: &GameData) -> Self {
+        let players = data.players.iter().map(|player| Entrant {
+            car: player.car.unwrap_or(CarChoice::Car1),
+            human: true,
+            points: 0,
+            last_place: None,
+        });
+        let opponents = (0..data.opponents).map(|i| Entrant {
+            car: CarChoice::ALL[i % CarChoice::ALL.len()],
+            human: false,
+            points: 0,
+            last_place: None,
+        });
+
+        Self {
+            tracks: TrackChoice::ALL.to_vec(),
+            races_run: 0,
+            laps: data.laps,
+            difficulty: data.difficulty,
+            entrants: players.chain(opponents).collect(),
+        }
+    }
+
+    // None when there's no cup in progress, or it can't be read
+    pub fn load() -> Option<Self> {
+        let text = fs::read_to_string(CHAMPIONSHIP_PATH).ok()?;
+
+        match Self::parse(&text) {
+            Ok(cup) => Some(cup),
+            Err(reason) => {
+                report(&AssetError::Invalid {
+                    path: CHAMPIONSHIP_PATH.to_string(),
+                    reason,
+                });
+                None
+            }
+        }
+    }
+
+    // `key value` lines, and one `entrant player|cpu <car> <points> <last>`
+    // line per driver, `last` being the place in the last race or `-`. Saves
+    // from before `last` was kept leave it off.
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut cup = Self {
+            tracks: TrackChoice::ALL.to_vec(),
+            races_run: 0,
+            laps: 3,
+            difficulty: Difficulty::Medium,
+            entrants: Vec::new(),
+        };
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = |what: &str| format!("line {}: {}", i + 1, what);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            match fields[..] {
+                ["races_run", count] => {
+                    cup.races_run = count.parse().map_err(|_| invalid("expected a count"))?;
+                }
+                ["laps", laps] => {
+                    cup.laps = laps
+                        .parse()
+                        .ok()
+                        .filter(|&laps| laps > 0)
+                        .ok_or_else(|| invalid("expected a lap count above 0"))?;
+                }
+                ["difficulty", name] => {
+                    cup.difficulty = Difficulty::ALL
+                        .into_iter()
+                        .find(|level| level.name() == name)
+                        .ok_or_else(|| invalid("unknown difficulty"))?;
+                }
+                ["entrant", driver, car, points, ref last @ ..] if last.len() <= 1 => {
+                    let human = match driver {
+                        "player" => true,
+                        "cpu" => false,
+                        _ => return Err(invalid("expected player or cpu")),
+                    };
+                    let car = car
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|n| n.checked_sub(1))
+                        .and_then(|i| CarChoice::ALL.get(i).copied())
+                        .ok_or_else(|| invalid("unknown car"))?;
+                    let points = points.parse().map_err(|_| invalid("expected points"))?;
+                    let last_place = match last.first() {
+                        None | Some(&"-") => None,
+                        Some(place) => Some(
+                            place
+                                .parse()
+                                .ok()
+                                .filter(|&place| place > 0)
+                                .ok_or_else(|| invalid("expected a place or -"))?,
+                        ),
+                    };
+                    cup.entrants.push(Entrant {
+                        car,
+                        human,
+                        points,
+                        last_place,
+                    });
+                }
+                _ => return Err(invalid("unknown entry")),
+            }
+        }
+
+        if !cup.entrants.iter().any(|entrant| entrant.human) {
+            return Err("no players in the cup".to_string());
+        }
+        cup.races_run = cup.races_run.min(cup.tracks.len());
+
+        Ok(cup)
+    }
+
+    pub fn save(&self) -> Result<(), AssetError> {
+        fs::write(CHAMPIONSHIP_PATH, self.to_text()).map_err(|e| AssetError::Invalid {
+            path: CHAMPIONSHIP_PATH.to_string(),
+            reason: e.to_string(),
+        })
+    }
+
+    // In the form `parse` reads
+    fn to_text(&self) -> String {
+        let mut text = String::from("# Championship in progress, removed when it ends\n");
+        let _ = writeln!(text, "races_run {}", self.races_run);
+        let _ = writeln!(text, "laps {}", self.laps);
+        let _ = writeln!(text, "difficulty {}", self.difficulty.name());
+        for entrant in &self.entrants {
+            let car = CarChoice::ALL
+                .iter()
+                .position(|&car| car == entrant.car)
+                .unwrap_or(0)
+                + 1;
+            let driver = if entrant.human { "player" } else { "cpu" };
+            let last = entrant
+                .last_place
+                .map_or("-".to_string(), |place| place.to_string());
+            let _ = writeln!(
+                text,
+                "entrant {} {} {} {}",
+                driver, car, entrant.points, last
+            );
+        }
+
+        text
+    }
+
+    // Once finished or abandoned there's nothing to resume
+    pub fn delete_save() {
+        let _ = fs::remove_file(CHAMPIONSHIP_PATH);
+    }
+
+    pub fn next_track(&self) -> Option<TrackChoice> {
+        self.tracks.get(self.races_run).copied()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.races_run >= self.tracks.len()
+    }
+
+    pub fn players(&self) -> usize {
+        self.entrants.iter().filter(|entrant| entrant.human).count()
+    }
+
+    // Set up the next race the way GameScene builds its field, so the same
+    // cars come back every time
+    pub fn prepare_race(&self, data: &mut GameData) {
+        let players: Vec<&Entrant> = self.entrants.iter().filter(|e| e.human).collect();
+        data.set_player_count(players.len());
+        for (player, entrant) in data.players.iter_mut().zip(players) {
+            player.car = Some(entrant.car);
+        }
+
+        data.set_opponents(self.entrants.len() - self.players());
+        data.selected_track = self.next_track();
+        data.laps = self.laps;
+        data.difficulty = self.difficulty;
+    }
+
+    // `places` is each entrant's finishing place, 1 for the winner
+    pub fn record_race(&mut self, places: &[usize]) {
+        for (entrant, &place) in self.entrants.iter_mut().zip(places) {
+            entrant.points += place
+                .checked_sub(1)
+                .and_then(|i| POINTS.get(i))
+                .copied()
+                .unwrap_or(0);
+            entrant.last_place = Some(place);
+        }
+        self.races_run += 1;
+    }
+
+    // Entrant indices, leader first. Ties go to the better last race.
+    pub fn standings(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.entrants.len()).collect();
+        order.sort_by_key(|&i| {
+            let entrant = &self.entrants[i];
+            (
+                std::cmp::Reverse(entrant.points),
+                entrant.last_place.unwrap_or(usize::MAX),
+            )
+        });
+        order
+    }
+
+    // "P1" for players and "CPU 1" for opponents
+    pub fn entrant_name(&self, index: usize) -> String {
+        let humans_before = self.entrants[..index].iter().filter(|e| e.human).count();
+        if self.entrants[index].human {
+            format!("P{}", humans_before + 1)
+        } else {
+            format!("CPU {}", index - humans_before + 1)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cup(drivers: usize) -> Championship {
+        Championship {
+            tracks: TrackChoice::ALL.to_vec(),
+            races_run: 0,
+            laps: 3,
+            difficulty: Difficulty::Medium,
+            entrants: (0..drivers)
+                .map(|i| Entrant {
+                    car: CarChoice::ALL[i % CarChoice::ALL.len()],
+                    human: i == 0,
+                    points: 0,
+                    last_place: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn points(cup: &Championship) -> Vec<u32> {
+        cup.entrants.iter().map(|entrant| entrant.points).collect()
+    }
+
+    #[test]
+    fn points_go_by_finishing_place() {
+        let mut cup = cup(4);
+        cup.record_race(&[2, 1, 4, 3]);
+
+        assert_eq!(points(&cup), [8, 10, 5, 6]);
+        assert_eq!(cup.entrants[2].last_place, Some(4));
+        assert_eq!(cup.races_run, 1);
+
+        cup.record_race(&[1, 2, 3, 4]);
+        assert_eq!(points(&cup), [18, 18, 11, 11]);
+    }
+
+    #[test]
+    fn places_beyond_the_points_score_nothing() {
+        let mut cup = cup(8);
+        cup.record_race(&[1, 2, 3, 4, 5, 6, 7, 8]);
+
+        assert_eq!(points(&cup), [10, 8, 6, 5, 4, 3, 0, 0]);
+        assert_eq!(cup.entrants[7].last_place, Some(8));
+    }
+
+    #[test]
+    fn ties_go_to_the_better_last_race() {
+        let mut cup = cup(3);
+        cup.record_race(&[1, 2, 3]);
+        cup.record_race(&[2, 1, 3]);
+
+        // Level on 18, the second won the last race
+        assert_eq!(points(&cup), [18, 18, 12]);
+        assert_eq!(cup.standings(), [1, 0, 2]);
+    }
+
+    #[test]
+    fn a_saved_cup_resumes_with_its_standings() {
+        let mut cup = cup(3);
+        cup.record_race(&[1, 2, 3]);
+        cup.record_race(&[2, 1, 3]);
+
+        let resumed = Championship::parse(&cup.to_text()).unwrap();
+
+        assert_eq!(resumed.races_run, 2);
+        assert_eq!(points(&resumed), [18, 18, 12]);
+        let last: Vec<_> = resumed.entrants.iter().map(|e| e.last_place).collect();
+        assert_eq!(last, [Some(2), Some(1), Some(3)]);
+        // Still broken on the last race
+        assert_eq!(resumed.standings(), [1, 0, 2]);
+    }
+
+    #[test]
+    fn a_cup_saved_before_any_race_has_no_last_places() {
+        let resumed = Championship::parse(&cup(2).to_text()).unwrap();
+        assert!(resumed.entrants.iter().all(|e| e.last_place.is_none()));
+
+        // Saves from before the last place was kept
+        let old = "races_run 1\nentrant player 1 10\nentrant cpu 2 8\n";
+        let resumed = Championship::parse(old).unwrap();
+        assert_eq!(points(&resumed), [10, 8]);
+        assert!(resumed.entrants.iter().all(|e| e.last_place.is_none()));
+
+        assert!(Championship::parse("entrant player 1 10 first\n").is_err());
+    }
+
+    #[test]
+    fn standings_lead_with_the_most_points() {
+        let mut cup = cup(3);
+        cup.record_race(&[3, 1, 2]);
+
+        assert_eq!(cup.standings(), [1, 2, 0]);
+    }
+}
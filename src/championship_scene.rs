@@ -0,0 +1,251 @@
+use raylib::prelude::*;
+
+use crate::assets::{TextureHandle, report};
+use crate::car::car_name;
+use crate::championship::{Championship, POINTS};
+use crate::display::content_top;
+use crate::game_data::GameData;
+use crate::game_scene::GameScene;
+use crate::input::read_menu_input;
+use crate::scenes::{Scene, SceneSwitch};
+use crate::track_manifest::track_name;
+use crate::ui::*;
+use crate::utils::ordinal;
+
+const TABLE_WIDTH: f32 = 640.0;
+const ROW_HEIGHT: f32 = 38.0;
+const PLAYER_ROW_COLOR: Color = Color::new(255, 250, 205, 230);
+const ROW_COLOR: Color = Color::new(245, 222, 179, 200);
+
+// -------- Standings between the races of a cup --------
+// Sits under each race, which pops back here once it's run. The cup
+// itself lives in GameData so the race can score it.
+pub struct ChampionshipScene {
+    background_texture: Option<TextureHandle>,
+
+    title: Label,
+    next_button: Button,
+    abandon_button: Button,
+    back_button: Button,
+    focus: Focus,
+    top: f32,
+    center_x: f32,
+}
+
+impl ChampionshipScene {
+    pub fn new(
+        _rl: &mut RaylibHandle,
+        _thread: &RaylibThread,
+        screen_width: i32,
+        screen_height: i32,
+    ) -> Self {
+        let mut scene = Self {
+            background_texture: None,
+            title: Label::new("Championship", 50),
+            next_button: Button::new("Next Race"),
+            abandon_button: Button::new("Abandon Cup").with_font_size(25),
+            back_button: Button::new("Back").with_font_size(25),
+            focus: Focus::default(),
+            top: 0.0,
+            center_x: 0.0,
+        };
+        scene.layout(screen_width, screen_height);
+        scene
+    }
+
+    fn layout(&mut self, screen_width: i32, screen_height: i32) {
+        self.center_x = screen_width as f32 / 2.0;
+        self.top = content_top(screen_height);
+
+        self.title.rect = Rectangle::new(0.0, self.top + 50.0, screen_width as f32, 50.0);
+
+        let mut column = Layout::column(self.center_x, self.top + 560.0, 320.0, 50.0, 16.0);
+        self.next_button.rect = column.next_rect();
+        self.abandon_button.rect = column.next_rect();
+
+        self.back_button.rect = Rectangle::new(20.0, 20.0, 120.0, 40.0);
+    }
+
+    fn draw_table(&self, d: &mut RaylibDrawHandle, cup: &Championship) {
+        let left = self.center_x - TABLE_WIDTH / 2.0;
+        let top = self.top + 170.0;
+        // Place, driver, car, last race, points
+        let columns = [0.0, 80.0, 200.0, 420.0, 540.0];
+        let text = |d: &mut RaylibDrawHandle, column: usize, y: f32, text: &str, size: i32| {
+            d.draw_text(
+                text,
+                (left + 15.0 + columns[column]) as i32,
+                y as i32,
+                size,
+                TEXT_COLOR,
+            );
+        };
+
+        let header = ["Pos", "Driver", "Car", "Last", "Points"];
+        for (column, title) in header.iter().enumerate() {
+            text(d, column, top, title, 20);
+        }
+
+        for (row, &i) in cup.standings().iter().enumerate() {
+            let entrant = &cup.entrants[i];
+            let y = top + 30.0 + row as f32 * ROW_HEIGHT;
+
+            let color = if entrant.human {
+                PLAYER_ROW_COLOR
+            } else {
+                ROW_COLOR
+            };
+            d.draw_rectangle_rounded(
+                Rectangle::new(left, y, TABLE_WIDTH, ROW_HEIGHT - 4.0),
+                0.3,
+                8,
+                color,
+            );
+
+            let y = y + 7.0;
+            let last = match entrant.last_place {
+                Some(place) => {
+                    let points = POINTS.get(place - 1).copied().unwrap_or(0);
+                    format!("{} +{}", ordinal(place), points)
+                }
+                None => "-".to_string(),
+            };
+            text(d, 0, y, &ordinal(row + 1), 22);
+            text(d, 1, y, &cup.entrant_name(i), 22);
+            text(d, 2, y, car_name(&entrant.car), 20);
+            text(d, 3, y, &last, 20);
+            text(d, 4, y, &entrant.points.to_string(), 22);
+        }
+    }
+}
+
+impl Scene for ChampionshipScene {
+    fn on_enter(&mut self, rl: &mut RaylibHandle, data: &mut GameData, thread: &RaylibThread) {
+        self.background_texture = Some(data.assets.texture(rl, thread, "Assets/selectBack1.png"));
+    }
+
+    fn on_resize(&mut self, data: &GameData) {
+        self.layout(data.screen_width, data.screen_height);
+    }
+
+    fn handle_input(
+        &mut self,
+        rl: &mut RaylibHandle,
+        data: &mut GameData,
+        thread: &RaylibThread,
+    ) -> SceneSwitch {
+        let nav = read_menu_input(rl);
+        if nav.back {
+            return SceneSwitch::Pop;
+        }
+
+        self.focus.navigate(&nav, &[1, 1, 1]);
+        self.next_button.focused = self.focus.is(0, 0);
+        self.abandon_button.focused = self.focus.is(1, 0);
+        self.back_button.focused = self.focus.is(2, 0);
+
+        let next_clicked = self.next_button.update(rl, &nav);
+        let abandon_clicked = self.abandon_button.update(rl, &nav);
+        let back_clicked = self.back_button.update(rl, &nav);
+        if next_clicked || abandon_clicked || back_clicked {
+            data.play_click();
+        }
+
+        // Left as it is, to be resumed from the select screen
+        if back_clicked {
+            return SceneSwitch::Pop;
+        }
+        if !(next_clicked || abandon_clicked) {
+            return SceneSwitch::None;
+        }
+
+        let Some(over) = data.championship.as_ref().map(Championship::is_over) else {
+            return SceneSwitch::Pop;
+        };
+
+        // Over, or given up on, so there's nothing left to resume
+        if abandon_clicked || over {
+            Championship::delete_save();
+            data.championship = None;
+            return SceneSwitch::Pop;
+        }
+
+        // Cloned as setting up the race needs the rest of GameData
+        if let Some(cup) = data.championship.clone() {
+            cup.prepare_race(data);
+            if let Err(error) = cup.save() {
+                report(&error);
+            }
+            println!("Cup race {} of {}", cup.races_run + 1, cup.tracks.len());
+            let game_scene = GameScene::new(rl, thread, Vector2::new(100.0, 100.0), 90.0);
+            return SceneSwitch::Push(Box::new(game_scene));
+        }
+
+        SceneSwitch::None
+    }
+
+    fn update(&mut self, _dt: f32, data: &mut GameData) -> SceneSwitch {
+        let over = data.championship.as_ref().is_none_or(|cup| cup.is_over());
+        self.next_button.label = if over { "Finish" } else { "Next Race" }.to_string();
+        self.abandon_button.enabled = !over;
+        SceneSwitch::None
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
+        d.clear_background(Color::WHITESMOKE);
+
+        if let Some(texture) = &self.background_texture {
+            draw_background(
+                d,
+                texture,
+                data.screen_width as f32,
+                data.screen_height as f32,
+            );
+        }
+
+        self.title.draw(d);
+
+        if let Some(cup) = &data.championship {
+            let subtitle = match cup.next_track() {
+                Some(track) => format!(
+                    "Race {} of {}: {}, {} laps",
+                    cup.races_run + 1,
+                    cup.tracks.len(),
+                    track_name(&track),
+                    cup.laps
+                ),
+                None => "Final Standings".to_string(),
+            };
+            draw_text_centered(
+                d,
+                &subtitle,
+                Rectangle::new(0.0, self.top + 115.0, data.screen_width as f32, 30.0),
+                28,
+                TEXT_COLOR,
+            );
+
+            self.draw_table(d, cup);
+
+            if cup.is_over()
+                && let Some(&leader) = cup.standings().first()
+            {
+                let text = format!("{} is the champion!", cup.entrant_name(leader));
+                draw_text_centered(
+                    d,
+                    &text,
+                    Rectangle::new(0.0, self.top + 500.0, data.screen_width as f32, 40.0),
+                    40,
+                    Color::GOLD,
+                );
+            }
+        }
+
+        self.next_button.draw(d);
+        self.abandon_button.draw(d);
+        self.back_button.draw(d);
+    }
+
+    fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {
+        self.background_texture = None;
+    }
+}
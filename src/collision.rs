@@ -0,0 +1,109 @@
+use raylib::prelude::*;
+
+use crate::car::{CAR_LENGTH, CAR_WIDTH, Car};
+
+// Each car is approximated by two circles, one over each axle
+const CIRCLE_RADIUS: f32 = CAR_WIDTH / 2.0;
+const CIRCLE_OFFSET: f32 = CAR_LENGTH / 2.0 - CIRCLE_RADIUS;
+
+const RESTITUTION: f32 = 0.4;
+// Degrees per second of spin per unit of angular impulse
+const SPIN_PER_IMPULSE: f32 = 0.1;
+
+struct Contact {
+    point: Vector2,
+    normal: Vector2,
+    depth: f32,
+}
+
+fn circles(car: &Car) -> [Vector2; 2] {
+    let forward = car.forward();
+    [
+        car.position + forward * CIRCLE_OFFSET,
+        car.position - forward * CIRCLE_OFFSET,
+    ]
+}
+
+// Deepest overlap between the circles of two cars, normal points from a to b
+fn find_contact(a: &Car, b: &Car) -> Option<Contact> {
+    let mut deepest: Option<Contact> = None;
+
+    for ca in circles(a) {
+        for cb in circles(b) {
+            let between = cb - ca;
+            let dist = between.length();
+            let depth = CIRCLE_RADIUS * 2.0 - dist;
+
+            if depth <= 0.0 || deepest.as_ref().is_some_and(|c| c.depth >= depth) {
+                continue;
+            }
+
+            let normal = if dist > f32::EPSILON {
+                between / dist
+            } else {
+                a.forward()
+            };
+
+            deepest = Some(Contact {
+                point: ca + normal * (CIRCLE_RADIUS - depth / 2.0),
+                normal,
+                depth,
+            });
+        }
+    }
+
+    deepest
+}
+
+fn cross(a: Vector2, b: Vector2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+// -------- Push overlapping cars apart and trade momentum --------
+// Returns the number of new hits this frame
+pub fn resolve_car_collisions(cars: &mut [Car]) -> u32 {
+    let mut hits = 0;
+
+    for i in 0..cars.len() {
+        for j in (i + 1)..cars.len() {
+            let (head, tail) = cars.split_at_mut(j);
+            let a = &mut head[i];
+            let b = &mut tail[0];
+            if a.retired || b.retired {
+                continue;
+            }
+
+            let Some(contact) = find_contact(a, b) else {
+                continue;
+            };
+
+            let inv_a = 1.0 / a.stats.mass;
+            let inv_b = 1.0 / b.stats.mass;
+            let inv_sum = inv_a + inv_b;
+
+            // Separate them so they stop overlapping, heavier cars move less
+            let push = contact.normal * (contact.depth / inv_sum);
+            a.position -= push * inv_a;
+            b.position += push * inv_b;
+
+            let closing = (b.velocity() - a.velocity()).dot(contact.normal);
+            if closing >= 0.0 {
+                continue;
+            }
+
+            let impulse = contact.normal * (-(1.0 + RESTITUTION) * closing / inv_sum);
+            a.add_velocity(-impulse * inv_a);
+            b.add_velocity(impulse * inv_b);
+
+            // Off-centre hits twist the car around
+            a.spin += cross(contact.point - a.position, -impulse) * inv_a * SPIN_PER_IMPULSE;
+            b.spin += cross(contact.point - b.position, impulse) * inv_b * SPIN_PER_IMPULSE;
+
+            a.collisions += 1;
+            b.collisions += 1;
+            hits += 1;
+        }
+    }
+
+    hits
+}
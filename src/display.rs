@@ -0,0 +1,300 @@
+use raylib::prelude::*;
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::assets::{AssetError, report};
+use crate::game_data::GameData;
+use crate::utils::contain_scale;
+
+// -------- The size races are laid out at --------
+// Tracks, viewports and the HUD all work in these units, and are scaled
+// to fit whatever size the window is, with bars either side if the shape
+// doesn't match.
+pub const VIRTUAL_WIDTH: i32 = 960;
+pub const VIRTUAL_HEIGHT: i32 = 720;
+
+// Menus are laid out for the virtual size, so the window can't go smaller
+pub const MIN_WINDOW_WIDTH: i32 = VIRTUAL_WIDTH;
+pub const MIN_WINDOW_HEIGHT: i32 = VIRTUAL_HEIGHT;
+
+pub const DISPLAY_SETTINGS_PATH: &str = "Assets/display.txt";
+
+// Window sizes offered in the settings, none below the minimum
+pub const RESOLUTIONS: [(i32, i32); 6] = [
+    (960, 720),
+    (1024, 768),
+    (1280, 720),
+    (1280, 960),
+    (1600, 900),
+    (1920, 1080),
+];
+
+// Frame rate caps offered in the settings, 0 is no cap
+pub const FPS_CAPS: [u32; 6] = [30, 60, 120, 144, 240, 0];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WindowMode {
+    Windowed,
+    Fullscreen,
+    // A window without decorations covering the whole monitor
+    Borderless,
+}
+
+impl WindowMode {
+    pub const ALL: [WindowMode; 3] = [Self::Windowed, Self::Fullscreen, Self::Borderless];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WindowMode::Windowed => "Windowed",
+            WindowMode::Fullscreen => "Fullscreen",
+            WindowMode::Borderless => "Borderless",
+        }
+    }
+}
+
+// -------- Window and frame settings, kept between runs --------
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DisplaySettings {
+    // Window size when windowed, fullscreen takes the monitor's
+    pub resolution: (i32, i32),
+    pub mode: WindowMode,
+    pub vsync: bool,
+    // 0 is no cap
+    pub fps_cap: u32,
+    // Share of the window's resolution the race is drawn at
+    pub render_scale: f32,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            resolution: RESOLUTIONS[0],
+            mode: WindowMode::Windowed,
+            vsync: true,
+            fps_cap: 0,
+            render_scale: 1.0,
+        }
+    }
+}
+
+impl DisplaySettings {
+    // The defaults on a first run, or if the file can't be read
+    pub fn load() -> Self {
+        let Ok(text) = fs::read_to_string(DISPLAY_SETTINGS_PATH) else {
+            return Self::default();
+        };
+
+        Self::parse(&text).unwrap_or_else(|reason| {
+            report(&AssetError::Invalid {
+                path: DISPLAY_SETTINGS_PATH.to_string(),
+                reason,
+            });
+            Self::default()
+        })
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut settings = Self::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = |reason: &str| format!("line {}: {}", i + 1, reason);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            match fields[..] {
+                ["resolution", width, height] => {
+                    let size = (width.parse(), height.parse());
+                    let (Ok(width), Ok(height)) = size else {
+                        return Err(invalid("expected a width and height"));
+                    };
+                    if width < MIN_WINDOW_WIDTH || height < MIN_WINDOW_HEIGHT {
+                        return Err(invalid(&format!(
+                            "expected at least {} {}",
+                            MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT
+                        )));
+                    }
+                    settings.resolution = (width, height);
+                }
+                ["mode", name] => {
+                    settings.mode = WindowMode::ALL
+                        .into_iter()
+                        .find(|mode| mode.name().eq_ignore_ascii_case(name))
+                        .ok_or(invalid(&format!("unknown mode '{}'", name)))?;
+                }
+                ["vsync", value] => {
+                    settings.vsync = match value {
+                        "on" => true,
+                        "off" => false,
+                        _ => return Err(invalid("expected on or off")),
+                    };
+                }
+                ["fps", value] => {
+                    settings.fps_cap = value
+                        .parse()
+                        .map_err(|_| invalid("expected a frame rate"))?;
+                }
+                ["render_scale", value] => {
+                    settings.render_scale = value
+                        .parse::<f32>()
+                        .map_err(|_| invalid("expected a number"))?
+                        .clamp(0.25, 1.0);
+                }
+                _ => return Err(invalid(&format!("unknown entry '{}'", line))),
+            }
+        }
+
+        Ok(settings)
+    }
+
+    pub fn save(&self) -> Result<(), AssetError> {
+        let mut text = String::from("# Display settings, written by the settings screen\n");
+        let _ = writeln!(
+            text,
+            "resolution {} {}",
+            self.resolution.0, self.resolution.1
+        );
+        let _ = writeln!(text, "mode {}", self.mode.name().to_lowercase());
+        let _ = writeln!(text, "vsync {}", if self.vsync { "on" } else { "off" });
+        let _ = writeln!(text, "fps {}", self.fps_cap);
+        let _ = writeln!(text, "render_scale {:.2}", self.render_scale);
+
+        fs::write(DISPLAY_SETTINGS_PATH, text).map_err(|e| AssetError::Invalid {
+            path: DISPLAY_SETTINGS_PATH.to_string(),
+            reason: e.to_string(),
+        })
+    }
+
+    // F11 goes between fullscreen and a window, whichever mode it was in
+    pub fn toggle_fullscreen(&mut self) {
+        self.mode = match self.mode {
+            WindowMode::Windowed => WindowMode::Fullscreen,
+            WindowMode::Fullscreen | WindowMode::Borderless => WindowMode::Windowed,
+        };
+    }
+
+    // Set the window up to match, the new size is picked up by the scene
+    // manager on the next frame
+    pub fn apply(&self, rl: &mut RaylibHandle) {
+        let borderless =
+            unsafe { ffi::IsWindowState(ffi::ConfigFlags::FLAG_BORDERLESS_WINDOWED_MODE as u32) };
+
+        let fullscreen = rl.is_window_fullscreen();
+        let resized = (rl.get_screen_width(), rl.get_screen_height()) != self.resolution;
+
+        // Each mode is entered from a plain window
+        if fullscreen && self.mode != WindowMode::Fullscreen {
+            rl.toggle_fullscreen();
+        }
+        if borderless && self.mode != WindowMode::Borderless {
+            rl.toggle_borderless_windowed();
+        }
+
+        let monitor = get_current_monitor();
+        let monitor_size = (get_monitor_width(monitor), get_monitor_height(monitor));
+
+        match self.mode {
+            // Left where it is unless it has to change size
+            WindowMode::Windowed if fullscreen || borderless || resized => {
+                let (width, height) = self.resolution;
+                rl.set_window_size(width, height);
+
+                // Centred on the monitor, but never with the title bar off the top
+                let origin = get_monitor_position(monitor);
+                rl.set_window_position(
+                    origin.x as i32 + ((monitor_size.0 - width) / 2).max(0),
+                    origin.y as i32 + ((monitor_size.1 - height) / 2).max(40),
+                );
+            }
+            WindowMode::Windowed => {}
+            WindowMode::Fullscreen => {
+                if !fullscreen {
+                    rl.set_window_size(monitor_size.0, monitor_size.1);
+                    rl.toggle_fullscreen();
+                }
+            }
+            WindowMode::Borderless => {
+                if !borderless {
+                    rl.toggle_borderless_windowed();
+                }
+            }
+        }
+
+        let vsync = WindowState::default().set_vsync_hint(true);
+        if self.vsync {
+            rl.set_window_state(vsync);
+        } else {
+            rl.clear_window_state(vsync);
+        }
+        rl.set_target_fps(self.fps_cap);
+    }
+
+    // Applied straight away and written out for next time
+    pub fn apply_and_save(&self, rl: &mut RaylibHandle) {
+        self.apply(rl);
+        if let Err(error) = self.save() {
+            report(&error);
+        }
+    }
+}
+
+pub fn format_resolution(index: f32) -> String {
+    let (width, height) = RESOLUTIONS[index as usize];
+    format!("{}x{}", width, height)
+}
+
+pub fn format_window_mode(index: f32) -> String {
+    WindowMode::ALL[index as usize].name().to_string()
+}
+
+pub fn format_fps_cap(index: f32) -> String {
+    match FPS_CAPS[index as usize] {
+        0 => "Unlimited".to_string(),
+        fps => fps.to_string(),
+    }
+}
+
+// Scale and offset from the virtual screen to the window
+pub fn letterbox(data: &GameData) -> (f32, Vector2) {
+    contain_scale(
+        data.screen_width as f32,
+        data.screen_height as f32,
+        VIRTUAL_WIDTH as f32,
+        VIRTUAL_HEIGHT as f32,
+    )
+}
+
+// Top of the menus, which sit in the middle of a taller window
+pub fn content_top(screen_height: i32) -> f32 {
+    ((screen_height - VIRTUAL_HEIGHT) / 2).max(0) as f32
+}
+
+// F11 or Alt+Enter
+pub fn fullscreen_pressed(rl: &RaylibHandle) -> bool {
+    let alt =
+        rl.is_key_down(KeyboardKey::KEY_LEFT_ALT) || rl.is_key_down(KeyboardKey::KEY_RIGHT_ALT);
+    rl.is_key_pressed(KeyboardKey::KEY_F11) || (alt && rl.is_key_pressed(KeyboardKey::KEY_ENTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_resolution_below_the_smallest_window_is_rejected() {
+        for line in [
+            "resolution 0 0",
+            "resolution -1280 720",
+            "resolution 320 200",
+        ] {
+            assert!(DisplaySettings::parse(line).is_err(), "{}", line);
+        }
+
+        let smallest = format!("resolution {} {}", MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT);
+        let settings = DisplaySettings::parse(&smallest).unwrap();
+        assert_eq!(settings.resolution, (MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT));
+    }
+}
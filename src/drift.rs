@@ -0,0 +1,269 @@
+use crate::car::{Car, METERS_PER_PIXEL};
+use crate::track::Surface;
+
+// How quickly a drifting car's travel swings round to its nose, per
+// second. Loose enough to hold a slide on tarmac.
+pub const DRIFT_GRIP: f32 = 2.5;
+
+// Less slip or speed than this isn't a drift
+const MIN_SLIP: f32 = 8.0;
+const MIN_SPEED: f32 = 5.0;
+// Points a second for each degree of slip at one metre a second
+const POINTS_RATE: f32 = 0.5;
+// A drift ends once it's been straight this long, and the next one keeps
+// the combo going if it starts within the window
+const DRIFT_GRACE: f32 = 0.4;
+const CHAIN_WINDOW: f32 = 2.0;
+const MAX_MULTIPLIER: u32 = 8;
+
+// Something worth flashing up on the HUD
+#[derive(Clone)]
+pub struct DriftEvent {
+    pub text: String,
+    pub good: bool,
+    // The car's race time when it happened
+    pub at: f32,
+}
+
+// -------- One car's score in a drift challenge --------
+// Points build up through each drift from slip angle times speed, and are
+// banked with the combo multiplier when it ends. Every drift chained on
+// raises the multiplier, and a hit or a wheel off the tarmac loses both
+// the drift under way and the multiplier. The tracks have no walls, so
+// hits are contacts with other cars.
+#[derive(Clone)]
+pub struct DriftScore {
+    pub total: f32,
+    // Points in the drift under way, not yet multiplied
+    pub current: f32,
+    pub multiplier: u32,
+    pub drifting: bool,
+    pub last_event: Option<DriftEvent>,
+    // Where the finished run went on the track's leaderboard, 1 the top
+    pub board_place: Option<usize>,
+    // Seconds since the car last had enough slip
+    straight_for: f32,
+    // The car's collision count, to notice new hits
+    hits: u32,
+}
+
+impl DriftScore {
+    pub fn new() -> Self {
+        Self {
+            total: 0.0,
+            current: 0.0,
+            multiplier: 1,
+            drifting: false,
+            last_event: None,
+            board_place: None,
+            straight_for: CHAIN_WINDOW,
+            hits: 0,
+        }
+    }
+
+    pub fn update(&mut self, car: &Car, dt: f32) {
+        if car.progress.finished {
+            self.bank(car.race_time);
+            return;
+        }
+
+        let hit = car.collisions > self.hits;
+        self.hits = car.collisions;
+        if hit {
+            self.lose(car.race_time, "Hit!");
+            return;
+        }
+        // Only tarmac scores, not ice, the run-off or the edge beyond it
+        if car.surface != Surface::Road {
+            self.lose(car.race_time, "Off track!");
+            return;
+        }
+
+        let slip = car.slip_angle();
+        let speed = car.speed.abs() * METERS_PER_PIXEL;
+
+        if slip >= MIN_SLIP && speed >= MIN_SPEED {
+            // Too long since the last one and the combo starts over
+            if !self.drifting && self.straight_for > CHAIN_WINDOW {
+                self.multiplier = 1;
+            }
+            self.drifting = true;
+            self.straight_for = 0.0;
+            self.current += slip.min(90.0) * speed * POINTS_RATE * dt;
+            return;
+        }
+
+        self.straight_for += dt;
+        if self.drifting && self.straight_for > DRIFT_GRACE {
+            self.bank(car.race_time);
+        }
+        if !self.drifting && self.straight_for > CHAIN_WINDOW {
+            self.multiplier = 1;
+        }
+    }
+
+    // Points the drift under way would bank now
+    pub fn pending(&self) -> f32 {
+        self.current * self.multiplier as f32
+    }
+
+    fn bank(&mut self, at: f32) {
+        if !self.drifting {
+            return;
+        }
+
+        let points = self.pending();
+        self.total += points;
+        self.last_event = Some(DriftEvent {
+            text: format!("+{} x{}", points.round() as i32, self.multiplier),
+            good: true,
+            at,
+        });
+
+        self.multiplier = (self.multiplier + 1).min(MAX_MULTIPLIER);
+        self.current = 0.0;
+        self.drifting = false;
+    }
+
+    fn lose(&mut self, at: f32, reason: &str) {
+        // Only worth a mention when there was something to lose
+        if self.drifting || self.multiplier > 1 {
+            self.last_event = Some(DriftEvent {
+                text: format!("{} Combo lost", reason),
+                good: false,
+                at,
+            });
+        }
+
+        self.current = 0.0;
+        self.multiplier = 1;
+        self.drifting = false;
+        self.straight_for = CHAIN_WINDOW;
+    }
+}
+
+impl Default for DriftScore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::CarChoice;
+    use raylib::prelude::Vector2;
+
+    const STEP: f32 = 0.1;
+
+    fn car() -> Car {
+        let mut car = Car::new(CarChoice::Car1, None, Vector2::zero(), 0.0);
+        car.speed = 20.0 / METERS_PER_PIXEL;
+        car
+    }
+
+    // Hold the slide, 0 for straight, for a while
+    fn drive(score: &mut DriftScore, car: &mut Car, slip: f32, seconds: f32) {
+        car.travel = car.direction - slip;
+        for _ in 0..(seconds / STEP).round() as usize {
+            car.race_time += STEP;
+            score.update(car, STEP);
+        }
+    }
+
+    #[test]
+    fn a_drift_banks_with_the_multiplier() {
+        let (mut score, mut car) = (DriftScore::new(), car());
+
+        drive(&mut score, &mut car, 30.0, 1.0);
+        assert!(score.drifting);
+        let points = score.current;
+        assert!((points - 30.0 * 20.0 * POINTS_RATE).abs() < 1.0);
+        assert_eq!(score.total, 0.0);
+
+        drive(&mut score, &mut car, 0.0, DRIFT_GRACE + STEP);
+        assert!(!score.drifting);
+        assert_eq!(score.total, points);
+        assert_eq!(score.multiplier, 2);
+        assert!(score.last_event.as_ref().is_some_and(|event| event.good));
+    }
+
+    #[test]
+    fn chained_drifts_raise_the_multiplier() {
+        let (mut score, mut car) = (DriftScore::new(), car());
+
+        drive(&mut score, &mut car, 30.0, 1.0);
+        drive(&mut score, &mut car, 0.0, 1.0);
+        let first = score.total;
+
+        drive(&mut score, &mut car, 30.0, 1.0);
+        let second = score.current;
+        drive(&mut score, &mut car, 0.0, 1.0);
+
+        assert_eq!(score.total, first + second * 2.0);
+        assert_eq!(score.multiplier, 3);
+    }
+
+    #[test]
+    fn the_multiplier_resets_after_the_chain_window() {
+        let (mut score, mut car) = (DriftScore::new(), car());
+
+        drive(&mut score, &mut car, 30.0, 1.0);
+        drive(&mut score, &mut car, 0.0, 1.0);
+        assert_eq!(score.multiplier, 2);
+
+        drive(&mut score, &mut car, 0.0, CHAIN_WINDOW);
+        assert_eq!(score.multiplier, 1);
+    }
+
+    #[test]
+    fn going_off_track_loses_the_combo() {
+        let (mut score, mut car) = (DriftScore::new(), car());
+
+        drive(&mut score, &mut car, 30.0, 1.0);
+        drive(&mut score, &mut car, 0.0, 1.0);
+        let banked = score.total;
+        drive(&mut score, &mut car, 30.0, 1.0);
+
+        car.surface = Surface::Grass;
+        drive(&mut score, &mut car, 30.0, STEP);
+
+        assert_eq!(score.total, banked);
+        assert_eq!(score.current, 0.0);
+        assert_eq!(score.multiplier, 1);
+        assert!(!score.drifting);
+        assert!(score.last_event.as_ref().is_some_and(|event| !event.good));
+    }
+
+    #[test]
+    fn a_hit_loses_the_combo() {
+        let (mut score, mut car) = (DriftScore::new(), car());
+
+        drive(&mut score, &mut car, 30.0, 1.0);
+        drive(&mut score, &mut car, 0.0, 1.0);
+        drive(&mut score, &mut car, 30.0, 1.0);
+
+        car.collisions += 1;
+        drive(&mut score, &mut car, 30.0, STEP);
+
+        assert_eq!(score.current, 0.0);
+        assert_eq!(score.multiplier, 1);
+        let event = score.last_event.as_ref().unwrap();
+        assert!(!event.good && event.text.starts_with("Hit!"));
+
+        // Only new hits count
+        drive(&mut score, &mut car, 30.0, 1.0);
+        assert!(score.drifting && score.current > 0.0);
+    }
+
+    #[test]
+    fn gentle_slip_is_not_a_drift() {
+        let (mut score, mut car) = (DriftScore::new(), car());
+
+        drive(&mut score, &mut car, MIN_SLIP / 2.0, 1.0);
+        drive(&mut score, &mut car, 0.0, 1.0);
+
+        assert_eq!(score.total, 0.0);
+        assert!(score.last_event.is_none());
+    }
+}
@@ -1,7 +1,24 @@
+use crate::assets::AssetCache;
+use crate::audio::{Effect, SoundEffects, Volumes};
+use crate::car::CarTable;
+use crate::championship::Championship;
+use crate::display::DisplaySettings;
+use crate::hot_reload::AssetWatcher;
+use crate::music::MusicPlayer;
+use crate::records::Records;
+use crate::track_generator::TrackSeed;
+
+pub const MAX_PLAYERS: usize = 4;
+// Players and computer opponents together, as many as the starting grid holds
+pub const MAX_CARS: usize = 6;
+pub const MAX_LAPS: u32 = 10;
+
 #[derive(Copy, Clone, PartialEq)]
 pub enum ControlChoice {
     Keyboard,
-    Controller,
+    Controller(i32),
+    KeyboardLeft,
+    KeyboardRight,
 }
 
 #[derive(Copy, Clone, PartialEq)]
@@ -12,39 +29,275 @@ pub enum CarChoice {
     Car4,
 }
 
+impl CarChoice {
+    pub const ALL: [CarChoice; 4] = [Self::Car1, Self::Car2, Self::Car3, Self::Car4];
+}
+
 #[derive(Copy, Clone, PartialEq)]
 pub enum TrackChoice {
     Track1,
     Track2,
     Track3,
     Track4,
+    // Saved from the track editor, numbered from 1
+    Custom(u8),
+    // Built from the seed each time it is raced
+    Random(TrackSeed),
+}
+
+impl TrackChoice {
+    // The bundled tracks, saved custom ones are listed by `track_manifest::available_tracks`
+    pub const ALL: [TrackChoice; 4] = [Self::Track1, Self::Track2, Self::Track3, Self::Track4];
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Self::Easy, Self::Medium, Self::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+        }
+    }
+
+    // Handed to AiDriver::new
+    pub fn skill(&self) -> f32 {
+        match self {
+            Self::Easy => 0.3,
+            Self::Medium => 0.65,
+            Self::Hard => 1.0,
+        }
+    }
 }
 
-pub struct GameData {
+#[derive(Copy, Clone, PartialEq)]
+pub enum GameMode {
+    // First past the flag after the set number of laps
+    Race,
+    // Alone on the track, laps against the track's medal times
+    TimeTrial,
+    // Points for each race of a cup against the same field
+    Championship,
+    // The last car is knocked out on each lap or timer until one is left
+    Elimination,
+    // Alone on the track, scoring points for sliding through the corners
+    Drift,
+    // Alone against a countdown that each checkpoint tops up
+    Rush,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 6] = [
+        Self::Race,
+        Self::TimeTrial,
+        Self::Championship,
+        Self::Elimination,
+        Self::Drift,
+        Self::Rush,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Race => "Race",
+            Self::TimeTrial => "Time Trial",
+            Self::Championship => "Championship",
+            Self::Elimination => "Elimination",
+            Self::Drift => "Drift",
+            Self::Rush => "Checkpoint Rush",
+        }
+    }
+
+    pub fn has_opponents(&self) -> bool {
+        !matches!(self, Self::TimeTrial | Self::Drift | Self::Rush)
+    }
+}
+
+// When the last car goes out in an elimination race
+#[derive(Copy, Clone, PartialEq)]
+pub enum Knockout {
+    // As the leader starts each new lap
+    EachLap,
+    // Every this many seconds once the race is under way
+    Every(u32),
+}
+
+impl Knockout {
+    pub const ALL: [Knockout; 4] = [
+        Self::EachLap,
+        Self::Every(20),
+        Self::Every(30),
+        Self::Every(45),
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::EachLap => "Each Lap".to_string(),
+            Self::Every(seconds) => format!("Every {}s", seconds),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct PlayerSetup {
+    pub car: Option<CarChoice>,
+    pub control: Option<ControlChoice>,
+}
+
+pub struct GameData<'aud> {
     pub laps: u32,
     pub screen_width: i32,
     pub screen_height: i32,
+    pub display: DisplaySettings,
 
-    pub selected_car: Option<CarChoice>,
+    pub players: Vec<PlayerSetup>,
     pub selected_track: Option<TrackChoice>,
 
-    pub selected_control: Option<ControlChoice>,
+    pub mode: GameMode,
+    // Computer cars joining the players, never more than MAX_CARS in all
+    pub opponents: usize,
+    pub difficulty: Difficulty,
+    pub knockout: Knockout,
+    // The cup being raced, if any
+    pub championship: Option<Championship>,
+
+    // Cars drive through each other, for time trials
+    pub ghost_cars: bool,
 
     pub race_time: f32,
     pub race_started: bool,
+
+    // Textures shared between scenes
+    pub assets: AssetCache,
+    pub car_table: CarTable,
+    pub records: Records,
+
+    // None without an audio device
+    pub sounds: Option<SoundEffects<'aud>>,
+    pub music: Option<MusicPlayer<'aud>>,
+    pub volumes: Volumes,
+
+    // Only set in dev mode, reloads assets edited while the game runs
+    pub watcher: Option<AssetWatcher>,
 }
 
-impl GameData {
+impl GameData<'_> {
     pub fn new(width: i32, height: i32) -> Self {
         Self {
-            laps: 0,
+            laps: 3,
             screen_width: width,
             screen_height: height,
-            selected_car: None,
+            display: DisplaySettings::default(),
+            players: vec![PlayerSetup {
+                car: None,
+                control: None,
+            }],
             selected_track: None,
-            selected_control: None,
+            mode: GameMode::Race,
+            opponents: 0,
+            difficulty: Difficulty::Medium,
+            knockout: Knockout::EachLap,
+            championship: None,
+            ghost_cars: false,
             race_time: 0.0,
             race_started: false,
+            assets: AssetCache::new(),
+            car_table: CarTable::default(),
+            records: Records::default(),
+            sounds: None,
+            music: None,
+            volumes: Volumes::default(),
+            watcher: None,
+        }
+    }
+
+    // Menus click when a widget is used, if there's an audio device
+    pub fn play_click(&self) {
+        if let Some(sounds) = &self.sounds {
+            sounds.play(Effect::Click, &self.volumes);
+        }
+    }
+
+    // Grow or shrink the player list, giving new players their own input device
+    pub fn set_player_count(&mut self, count: usize) {
+        let count = count.clamp(1, MAX_PLAYERS);
+
+        while self.players.len() < count {
+            let index = self.players.len();
+            self.players.push(PlayerSetup {
+                car: None,
+                control: Some(default_control(index)),
+            });
+        }
+        self.players.truncate(count);
+        self.set_opponents(self.opponents);
+
+        // The combined keyboard scheme would steal the second player's keys
+        if count > 1
+            && self.players[0]
+                .control
+                .is_none_or(|c| c == ControlChoice::Keyboard)
+        {
+            self.players[0].control = Some(ControlChoice::KeyboardLeft);
+        }
+    }
+
+    pub fn set_opponents(&mut self, count: usize) {
+        self.opponents = count.min(MAX_CARS - self.players.len());
+    }
+
+    // Cars on the grid, players and opponents
+    pub fn field_size(&self) -> usize {
+        let opponents = if self.mode.has_opponents() {
+            self.opponents
+        } else {
+            0
+        };
+        self.players.len() + opponents
+    }
+
+    // What Play needs: a track, unless a cup picks them, a car for every
+    // player, and someone to knock out in an elimination race
+    pub fn ready_to_race(&self) -> bool {
+        (self.selected_track.is_some() || self.mode == GameMode::Championship)
+            && self.players.iter().all(|player| player.car.is_some())
+            && (self.mode != GameMode::Elimination || self.field_size() > 1)
+    }
+}
+
+pub fn default_control(player: usize) -> ControlChoice {
+    match player {
+        0 => ControlChoice::KeyboardLeft,
+        1 => ControlChoice::KeyboardRight,
+        n => ControlChoice::Controller(n as i32 - 2),
+    }
+}
+
+pub fn control_name(control: Option<ControlChoice>) -> String {
+    match control {
+        Some(ControlChoice::Keyboard) | None => "Keyboard".to_string(),
+        Some(ControlChoice::KeyboardLeft) => "WASD".to_string(),
+        Some(ControlChoice::KeyboardRight) => "Arrows".to_string(),
+        Some(ControlChoice::Controller(pad)) => format!("Pad {}", pad + 1),
+    }
+}
+
+// Cycle to the next input device for a player in SelectScene
+pub fn next_control(control: Option<ControlChoice>) -> ControlChoice {
+    match control {
+        Some(ControlChoice::Keyboard) | None => ControlChoice::KeyboardLeft,
+        Some(ControlChoice::KeyboardLeft) => ControlChoice::KeyboardRight,
+        Some(ControlChoice::KeyboardRight) => ControlChoice::Controller(0),
+        Some(ControlChoice::Controller(pad)) if pad < MAX_PLAYERS as i32 - 1 => {
+            ControlChoice::Controller(pad + 1)
         }
+        Some(ControlChoice::Controller(_)) => ControlChoice::Keyboard,
     }
 }
@@ -1,28 +1,70 @@
-use crate::game_data::{CarChoice, ControlChoice, GameData, TrackChoice};
+use crate::ai::AiDriver;
+use crate::assets::{TextureHandle, report};
+use crate::audio::Effect;
+use crate::car::{CAR_DATA_PATH, Car, CarSprites, car_class, car_name, car_sprite_paths};
+use crate::display::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH, letterbox};
+use crate::drift::{DRIFT_GRIP, DriftScore};
+use crate::game_data::{CarChoice, GameData, GameMode, Knockout, TrackChoice};
+use crate::hud::{DriftStatus, HudInfo, KnockoutStatus, MedalPace, RushStatus, draw_hud};
+use crate::input::{read_car_input, read_menu_input, restart_pressed};
+use crate::medals::{Medal, MedalTargets};
+use crate::music::Playlist;
+use crate::race::{grid_position, offset_spawn_for_car, race_places, step_race};
+use crate::rush::RushClock;
 use crate::scenes::{Scene, SceneSwitch};
+use crate::track::{Checkpoint, Surface, TrackLayout, lap_fraction, surface_at, track_path};
+use crate::track_manifest::{
+    TrackManifest, custom_manifest_path, load_track, medal_targets, track_name,
+};
+use crate::utils::*;
 use raylib::prelude::*;
 
-const CAR_SCALE: f32 = 0.05;
-const CAR_SPRITE_ROT_OFFSET: f32 = -90.0; // sprite artwork faces up
-
-struct CarSprites {
-    straight: Texture2D,
-    left: Texture2D,
-    right: Texture2D,
-}
-
 pub struct GameScene {
-    player_position: Vector2,
-    player_direction: f32,
-    player_speed: f32,
-    player_acceleration: f32,
-    player_rot_vel: f32,
+    spawn_position: Vector2,
+    spawn_direction: f32,
+
+    // The players' cars come first, then the computer opponents
+    cars: Vec<Car>,
+    players: usize,
+    ai: AiDriver,
+    checkpoints: Vec<Checkpoint>,
 
-    track_texture: Option<Texture2D>,
+    track_texture: Option<TextureHandle>,
     track_image: Option<Image>,
-    car_sprites: Option<CarSprites>,
+    // The players' views, drawn at the display's render scale
+    race_view: Option<RenderTexture2D>,
+
+    // Set when driving the editor's unsaved track, which ends back there
+    test_track: Option<(Image, TrackManifest)>,
+    test_drive: bool,
+
+    // Name the track's best times are kept under, test drives don't count
+    records_key: Option<String>,
+    // Medal times for a time trial, empty in other modes
+    targets: MedalTargets,
+    // Set once a cup race's places have been scored
+    cup_scored: bool,
+
+    // Elimination races only, when the last car goes out
+    knockout: Option<Knockout>,
+    // Cars knocked out so far on the leader's laps, and time towards the
+    // next timed one
+    lap_knockouts: u32,
+    knockout_timer: f32,
+    // Race time each car was knocked out, for fading it away
+    knocked_out_at: Vec<Option<f32>>,
+
+    // Each player's score in a drift challenge, empty in other modes
+    drift: Vec<DriftScore>,
+    // Each player's countdown in a checkpoint rush, empty in other modes
+    rush: Vec<RushClock>,
 }
 
+// Cache id of the editor's track, which has no file of its own
+const TEST_TRACK_ID: &str = "<editor track>";
+// Seconds a knocked out car takes to fade away
+const FADE_OUT: f32 = 1.5;
+
 impl GameScene {
     pub fn new(
         _rl: &mut RaylibHandle,
@@ -31,53 +73,40 @@ impl GameScene {
         player_direction: f32,
     ) -> Self {
         Self {
-            player_position,
-            player_direction,
-            player_speed: 0.0,
-            player_acceleration: 0.0,
-            player_rot_vel: 0.0,
+            spawn_position: player_position,
+            spawn_direction: player_direction,
+            cars: Vec::new(),
+            players: 0,
+            ai: AiDriver::new(1.0),
+            checkpoints: Vec::new(),
             track_texture: None,
             track_image: None,
-            car_sprites: None,
+            race_view: None,
+            test_track: None,
+            test_drive: false,
+            records_key: None,
+            targets: MedalTargets::default(),
+            cup_scored: false,
+            knockout: None,
+            lap_knockouts: 0,
+            knockout_timer: 0.0,
+            knocked_out_at: Vec::new(),
+            drift: Vec::new(),
+            rush: Vec::new(),
         }
     }
 
-    // -------- Find first white pixel on track --------
-    fn find_spawn_pixel(
-        image: &mut Image,
-        screen_w: f32,
-        screen_h: f32,
-        tex_w: f32,
-        tex_h: f32,
-    ) -> Vector2 {
-        let scale = (screen_w / tex_w).max(screen_h / tex_h);
-
-        for y in 0..image.height {
-            for x in 0..image.width {
-                let c = image.get_color(x, y);
-
-                if c.r > 200 && c.g > 200 && c.b > 200 {
-                    return Vector2 {
-                        x: (x as f32 * scale) + (screen_w - tex_w * scale) / 2.0,
-                        y: (y as f32 * scale) + (screen_h - tex_h * scale) / 2.0 + 35.0,
-                    };
-                }
-            }
-        }
-
-        Vector2 {
-            x: screen_w / 2.0,
-            y: screen_h / 2.0,
-        }
-    }
-
-    // -------- Offset spawn so entire car is on white --------
-    fn offset_spawn_for_car(spawn: Vector2, direction_deg: f32, car_tex: &Texture2D) -> Vector2 {
-        let half_len = (car_tex.width as f32 * CAR_SCALE) / 2.0;
-        let rad = direction_deg.to_radians();
-        let backward = Vector2::new(rad.cos(), rad.sin());
-
-        spawn - backward * half_len
+    // Race round a track straight from the editor
+    pub fn test_drive(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        image: Image,
+        manifest: TrackManifest,
+    ) -> Self {
+        let mut scene = Self::new(rl, thread, manifest.spawn, manifest.direction);
+        scene.test_track = Some((image, manifest));
+        scene.test_drive = true;
+        scene
     }
 
     fn load_track(
@@ -85,148 +114,569 @@ impl GameScene {
         rl: &mut RaylibHandle,
         thread: &RaylibThread,
         track: &TrackChoice,
-        data: &GameData,
+        data: &mut GameData,
+    ) {
+        // Generated tracks have no file, they are built to fit the virtual screen
+        let (image, layout) = load_track(track, VIRTUAL_WIDTH as f32, VIRTUAL_HEIGHT as f32);
+        self.use_track(rl, thread, &track_path(track), image, layout, data);
+    }
+
+    fn use_track(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        id: &str,
+        image: Image,
+        layout: TrackLayout,
+        data: &mut GameData,
     ) {
-        let path = track_path(track);
+        let texture = data.assets.texture_from_image(rl, thread, id, &image);
 
-        let mut image = Image::load_image(path).expect("Failed to load track image");
+        self.spawn_position = layout.spawn;
+        self.spawn_direction = layout.direction;
+        self.checkpoints = layout.checkpoints;
 
-        let texture = rl
-            .load_texture(thread, path)
-            .expect("Failed to load track texture");
+        self.track_image = Some(image);
+        self.track_texture = Some(texture);
+    }
 
-        let spawn = Self::find_spawn_pixel(
-            &mut image,
-            data.screen_width as f32,
-            data.screen_height as f32,
-            texture.width as f32,
-            texture.height as f32,
+    // -------- Split the virtual screen between players --------
+    fn viewport(index: usize, count: usize) -> Rectangle {
+        let w = VIRTUAL_WIDTH as f32;
+        let h = VIRTUAL_HEIGHT as f32;
+
+        match count {
+            1 => Rectangle::new(0.0, 0.0, w, h),
+            2 => Rectangle::new(0.0, index as f32 * h / 2.0, w, h / 2.0),
+            _ => Rectangle::new(
+                (index % 2) as f32 * w / 2.0,
+                (index / 2) as f32 * h / 2.0,
+                w / 2.0,
+                h / 2.0,
+            ),
+        }
+    }
+
+    fn camera_for(car: &Car, view: Rectangle, count: usize) -> Camera2D {
+        if count == 1 {
+            return Camera2D {
+                offset: Vector2::zero(),
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: 1.0,
+            };
+        }
+
+        // Follow the car but keep the view inside the track image
+        let half_w = view.width / 2.0;
+        let half_h = view.height / 2.0;
+        let target = Vector2::new(
+            car.position
+                .x
+                .clamp(half_w, (VIRTUAL_WIDTH as f32 - half_w).max(half_w)),
+            car.position
+                .y
+                .clamp(half_h, (VIRTUAL_HEIGHT as f32 - half_h).max(half_h)),
         );
 
-        self.player_position = spawn;
-        self.player_direction = 180.0; // FACE LEFT
+        Camera2D {
+            offset: Vector2::new(view.x + half_w, view.y + half_h),
+            target,
+            rotation: 0.0,
+            zoom: 1.0,
+        }
+    }
 
-        self.track_image = Some(image);
-        self.track_texture = Some(texture);
+    // `focus` is the car whose viewport this is, other ghost cars are faded
+    fn draw_world<D: RaylibDraw>(&self, d: &mut D, focus: Option<usize>, data: &GameData) {
+        if let Some(track) = self.track_texture.as_deref() {
+            let tex_w = track.width as f32;
+            let tex_h = track.height as f32;
+            let win_w = VIRTUAL_WIDTH as f32;
+            let win_h = VIRTUAL_HEIGHT as f32;
+            let (scale, offset) = cover_scale(win_w, win_h, tex_w, tex_h);
+
+            d.draw_texture_pro(
+                track,
+                Rectangle::new(0.0, 0.0, tex_w, tex_h),
+                Rectangle {
+                    x: offset.x,
+                    y: offset.y,
+                    width: tex_w * scale,
+                    height: tex_h * scale,
+                },
+                Vector2::zero(),
+                0.0,
+                Color::WHITE,
+            );
+        }
+
+        // Start / finish line
+        if let Some(start) = self.checkpoints.first() {
+            d.draw_line_ex(start.start, start.end, 4.0, Color::RED);
+        }
+
+        for (i, car) in self.cars.iter().enumerate() {
+            let mut alpha = if data.ghost_cars && focus != Some(i) {
+                0.5
+            } else {
+                1.0
+            };
+            if let Some(at) = self.knocked_out_at[i] {
+                alpha *= 1.0 - (data.race_time - at) / FADE_OUT;
+            }
+            if alpha > 0.0 {
+                car.draw(d, Color::WHITE.alpha(alpha));
+            }
+        }
     }
 
-    fn load_car_sprites(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, car: &CarChoice) {
-        let (folder, base) = match car {
-            CarChoice::Car1 => ("Assets/frames/car1", "1"),
-            CarChoice::Car2 => ("Assets/frames/car2", "2"),
-            CarChoice::Car3 => ("Assets/frames/car3", "3"),
-            CarChoice::Car4 => ("Assets/frames/car4", "4"),
+    // Best laps of the given cars, and race times of those that finished.
+    // Drift runs are on looser physics, so their times don't count.
+    fn save_records(&self, data: &mut GameData, cars: &[usize]) {
+        let Some(key) = &self.records_key else {
+            return;
         };
+        if data.mode == GameMode::Drift {
+            return;
+        }
 
-        self.car_sprites = Some(CarSprites {
-            straight: rl
-                .load_texture(thread, &format!("{}/{}.png", folder, base))
-                .unwrap(),
-            left: rl
-                .load_texture(thread, &format!("{}/{}_left.png", folder, base))
-                .unwrap(),
-            right: rl
-                .load_texture(thread, &format!("{}/{}_right.png", folder, base))
-                .unwrap(),
-        });
+        let mut improved = false;
+        for &i in cars {
+            let progress = &self.cars[i].progress;
+            let race = progress.finished.then_some(self.cars[i].race_time);
+            improved |= data
+                .records
+                .record(key, data.laps, data.mode, progress.best_lap, race);
+        }
+
+        if improved && let Err(error) = data.records.save() {
+            report(&error);
+        }
     }
-}
 
-impl Scene for GameScene {
-    fn on_enter(&mut self, rl: &mut RaylibHandle, data: &mut GameData, thread: &RaylibThread) {
+    // Medals for the players' best laps so far in a time trial
+    fn award_medals(&self, data: &mut GameData) {
+        let Some(key) = &self.records_key else {
+            return;
+        };
+
+        let mut improved = false;
+        for car in &self.cars[..self.players] {
+            let class = car_class(&car.choice);
+            let earned = self
+                .targets
+                .get(class)
+                .zip(car.progress.best_lap)
+                .and_then(|(times, best)| times.medal_for(best));
+            if let Some(medal) = earned
+                && data.records.award(key, class, medal)
+            {
+                println!("{} medal in class {}", medal.name(), class.name());
+                improved = true;
+            }
+        }
+
+        if improved && let Err(error) = data.records.save() {
+            report(&error);
+        }
+    }
+
+    // Scores of the given players, only counted over a finished run like
+    // race times
+    fn save_drift_scores(&mut self, data: &mut GameData, players: &[usize]) {
+        let Some(key) = &self.records_key else {
+            return;
+        };
+
+        let mut improved = false;
+        for &i in players {
+            let score = &mut self.drift[i];
+            score.board_place = data.records.record_drift(key, score.total);
+            improved |= score.board_place.is_some();
+        }
+
+        if improved && let Err(error) = data.records.save() {
+            report(&error);
+        }
+    }
+
+    // Pace against the next medal up from the one held for this car's class
+    fn medal_pace(&self, car: &Car, data: &GameData) -> Option<MedalPace> {
+        let class = car_class(&car.choice);
+        let times = self.targets.get(class)?;
+        let held = self
+            .records_key
+            .as_ref()
+            .and_then(|key| data.records.medal(key, class));
+        let target = Medal::next_target(held);
+
+        let lap_time = car.race_time - car.progress.lap_start;
+        let fraction = lap_fraction(
+            &self.checkpoints,
+            car.progress.next_checkpoint,
+            car.position,
+        );
+
+        Some(MedalPace {
+            target,
+            held,
+            delta: lap_time - times.time(target) * fraction,
+        })
+    }
+
+    // Everyone back on the grid with the clock reset
+    fn spawn_cars(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, data: &mut GameData) {
+        // Opponents line up ahead of the players, each car in turn. Test
+        // drives and time trials go alone.
+        let opponents = if self.test_drive || !data.mode.has_opponents() {
+            0
+        } else {
+            data.opponents
+        };
+        self.players = data.players.len();
+        self.ai = AiDriver::new(data.difficulty.skill());
+
+        let count = self.players + opponents;
+        let entries = data
+            .players
+            .iter()
+            .map(|player| (player.car.unwrap_or(CarChoice::Car1), player.control))
+            .chain((0..opponents).map(|i| (CarChoice::ALL[i % CarChoice::ALL.len()], None)));
+        self.cars = entries
+            .enumerate()
+            .map(|(i, (car, control))| {
+                let slot = (i + opponents) % count;
+                let position =
+                    grid_position(self.spawn_position, self.spawn_direction, slot, count);
+
+                let mut car = Car::new(car, control, position, self.spawn_direction);
+                car.stats = data.car_table.stats(&car.choice);
+                if data.mode == GameMode::Drift {
+                    car.drift_grip = Some(DRIFT_GRIP);
+                }
+                car.sprites = Some(CarSprites::load(rl, thread, &mut data.assets, &car.choice));
+
+                // ---- Final spawn correction (FULLY ON WHITE) ----
+                car.position = offset_spawn_for_car(car.position, car.direction);
+                car
+            })
+            .collect();
+
         data.race_time = 0.0;
         data.race_started = false;
+        self.cup_scored = false;
+
+        // Test drives go alone, so there'd be nobody to knock out
+        self.knockout =
+            (data.mode == GameMode::Elimination && !self.test_drive).then_some(data.knockout);
+        self.lap_knockouts = 0;
+        self.knockout_timer = 0.0;
+        self.knocked_out_at = vec![None; self.cars.len()];
+
+        self.drift = if data.mode == GameMode::Drift {
+            vec![DriftScore::new(); self.players]
+        } else {
+            Vec::new()
+        };
+        self.rush = if data.mode == GameMode::Rush {
+            (0..self.players).map(|_| RushClock::new()).collect()
+        } else {
+            Vec::new()
+        };
+    }
 
-        if let Some(track) = &data.selected_track {
-            self.load_track(rl, thread, track, data);
+    // Knock out the last car when the leader starts a new lap, or the
+    // timer runs out
+    fn run_knockouts(&mut self, dt: f32, data: &GameData) {
+        let Some(knockout) = self.knockout else {
+            return;
+        };
+
+        let due = match knockout {
+            Knockout::EachLap => {
+                let leader_laps = self
+                    .cars
+                    .iter()
+                    .filter(|car| !car.retired)
+                    .map(|car| car.progress.lap - 1)
+                    .max()
+                    .unwrap_or(0);
+                leader_laps > self.lap_knockouts
+            }
+            Knockout::Every(seconds) => {
+                if data.race_started {
+                    self.knockout_timer += dt;
+                }
+                self.knockout_timer >= seconds as f32
+            }
+        };
+        if !due {
+            return;
         }
 
-        let car = data.selected_car.unwrap_or(CarChoice::Car1);
-        self.load_car_sprites(rl, thread, &car);
+        match knockout {
+            Knockout::EachLap => self.lap_knockouts += 1,
+            Knockout::Every(seconds) => self.knockout_timer -= seconds as f32,
+        }
 
-        // ---- Final spawn correction (FULLY ON WHITE) ----
-        if let Some(sprites) = &self.car_sprites {
-            self.player_position = Self::offset_spawn_for_car(
-                self.player_position,
-                self.player_direction,
-                &sprites.straight,
+        let places = race_places(&self.cars, &self.checkpoints);
+        let last = (0..self.cars.len())
+            .filter(|&i| !self.cars[i].retired)
+            .max_by_key(|&i| places[i]);
+        if let Some(i) = last {
+            let car = &mut self.cars[i];
+            car.retire();
+            self.knocked_out_at[i] = Some(data.race_time);
+            println!(
+                "{} knocked out, {}",
+                car_name(&car.choice),
+                ordinal(places[i])
             );
         }
     }
 
+    // Top up each player's clock at the gates, and stop those that have
+    // run out, keeping their score if it's the track's best
+    fn run_rush_clocks(&mut self, data: &mut GameData) {
+        let mut out = Vec::new();
+        for (i, clock) in self.rush.iter_mut().enumerate() {
+            let car = &mut self.cars[i];
+            if car.retired {
+                continue;
+            }
+
+            clock.update(car, &self.checkpoints);
+            if clock.out_of_time(car) {
+                car.retire();
+                println!("Time up, {} checkpoints", clock.gates);
+                out.push(i);
+            }
+        }
+
+        let Some(key) = &self.records_key else {
+            return;
+        };
+        let mut improved = false;
+        for i in out {
+            improved |= data.records.record_rush(key, self.rush[i].gates as u32);
+        }
+        if improved && let Err(error) = data.records.save() {
+            report(&error);
+        }
+    }
+
+    fn cars_left(&self) -> usize {
+        self.cars.iter().filter(|car| !car.retired).count()
+    }
+
+    // What a player's HUD warns of in an elimination race
+    fn knockout_status(&self, index: usize, place: usize) -> Option<KnockoutStatus> {
+        let knockout = self.knockout?;
+        let car = &self.cars[index];
+        let left = self.cars_left();
+
+        Some(KnockoutStatus {
+            in_danger: !car.retired && left > 1 && place == left,
+            next_in: match knockout {
+                Knockout::Every(seconds) => Some(seconds as f32 - self.knockout_timer),
+                Knockout::EachLap => None,
+            },
+            out: car.retired,
+            won: !car.retired && left == 1,
+        })
+    }
+
+    // Points for every car once the players are home, opponents still out
+    // are placed by how far round they got
+    fn score_cup(&mut self, data: &mut GameData) {
+        self.cup_scored = true;
+        let Some(cup) = &mut data.championship else {
+            return;
+        };
+
+        cup.record_race(&race_places(&self.cars, &self.checkpoints));
+        if let Err(error) = cup.save() {
+            report(&error);
+        }
+    }
+
+    // An elimination race is over with one car left, or every player out,
+    // and a checkpoint rush once every clock has run down
+    fn race_over(&self) -> bool {
+        let players = &self.cars[..self.players];
+        if self.knockout.is_some() {
+            return self.cars_left() <= 1 || players.iter().all(|car| car.retired);
+        }
+        if !self.rush.is_empty() {
+            return players.iter().all(|car| car.retired);
+        }
+
+        self.players > 0 && players.iter().all(|car| car.progress.finished)
+    }
+
+    // Each player's view, plus the whole track in the quarter that three
+    // players leave spare
+    fn views(&self) -> Vec<(Rectangle, Camera2D, Option<usize>)> {
+        let count = self.players;
+
+        let mut views: Vec<(Rectangle, Camera2D, Option<usize>)> = self.cars[..count]
+            .iter()
+            .enumerate()
+            .map(|(i, car)| {
+                let view = Self::viewport(i, count);
+                (view, Self::camera_for(car, view, count), Some(i))
+            })
+            .collect();
+
+        if count == 3 {
+            let view = Self::viewport(3, 4);
+            let overview = Camera2D {
+                offset: Vector2::new(view.x, view.y),
+                target: Vector2::zero(),
+                rotation: 0.0,
+                zoom: 0.5,
+            };
+            views.push((view, overview, None));
+        }
+
+        views
+    }
+}
+
+impl Scene for GameScene {
+    fn on_enter(&mut self, rl: &mut RaylibHandle, data: &mut GameData, thread: &RaylibThread) {
+        if let Some(music) = &mut data.music {
+            music.play(Playlist::Race);
+        }
+
+        if let Some((mut image, manifest)) = self.test_track.take() {
+            let layout = manifest.layout(&mut image, VIRTUAL_WIDTH as f32, VIRTUAL_HEIGHT as f32);
+
+            // The editor may have changed it since the last test drive
+            data.assets.invalidate(TEST_TRACK_ID);
+            self.use_track(rl, thread, TEST_TRACK_ID, image, layout, data);
+        } else if let Some(track) = data.selected_track {
+            self.load_track(rl, thread, &track, data);
+            self.records_key = Some(track_name(&track));
+            if data.mode == GameMode::TimeTrial {
+                self.targets = medal_targets(&track);
+            }
+        }
+
+        self.spawn_cars(rl, thread, data);
+    }
+
     fn handle_input(
         &mut self,
         rl: &mut RaylibHandle,
         data: &mut GameData,
-        _thread: &RaylibThread,
+        thread: &RaylibThread,
     ) -> SceneSwitch {
-        self.player_acceleration = 0.0;
-        self.player_rot_vel = 0.0;
-
-        match data.selected_control {
-            Some(ControlChoice::Keyboard) | None => {
-                if rl.is_key_down(KeyboardKey::KEY_W) || rl.is_key_down(KeyboardKey::KEY_UP) {
-                    self.player_acceleration = 1.0;
-                } else if rl.is_key_down(KeyboardKey::KEY_S)
-                    || rl.is_key_down(KeyboardKey::KEY_DOWN)
-                {
-                    self.player_acceleration = -1.0;
-                }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            return SceneSwitch::Pop;
+        }
 
-                if rl.is_key_down(KeyboardKey::KEY_D) || rl.is_key_down(KeyboardKey::KEY_RIGHT) {
-                    self.player_rot_vel = 1.0;
-                } else if rl.is_key_down(KeyboardKey::KEY_A)
-                    || rl.is_key_down(KeyboardKey::KEY_LEFT)
-                {
-                    self.player_rot_vel = -1.0;
-                }
+        // Modes driven alone go straight back to the grid, keeping any best
+        // lap set so far
+        if !data.mode.has_opponents() && restart_pressed(rl) {
+            let players: Vec<usize> = (0..self.players).collect();
+            self.save_records(data, &players);
+            if let Some(sounds) = &data.sounds {
+                sounds.stop_cars();
             }
-            Some(ControlChoice::Controller) => {}
+            self.spawn_cars(rl, thread, data);
+            return SceneSwitch::None;
+        }
+
+        // Cup races go back to the standings
+        if self.race_over() && read_menu_input(rl).confirm {
+            return if self.test_drive || data.mode == GameMode::Championship {
+                SceneSwitch::Pop
+            } else {
+                SceneSwitch::PopToRoot
+            };
+        }
+
+        for (i, car) in self.cars.iter_mut().enumerate() {
+            let input = if i < self.players {
+                read_car_input(rl, car.control)
+            } else {
+                self.ai.drive(car, &self.checkpoints)
+            };
+            car.apply_input(input);
         }
 
         SceneSwitch::None
     }
 
     fn update(&mut self, dt: f32, data: &mut GameData) -> SceneSwitch {
-        if !data.race_started && self.player_speed.abs() > 1.0 {
-            data.race_started = true;
-        }
+        let (screen_w, screen_h) = (VIRTUAL_WIDTH as f32, VIRTUAL_HEIGHT as f32);
+        let track_image = &mut self.track_image;
+        let was_finished: Vec<bool> = self.cars.iter().map(|car| car.progress.finished).collect();
+
+        let hits = step_race(
+            &mut self.cars,
+            &self.checkpoints,
+            |point| match track_image {
+                Some(image) => surface_at(image, screen_w, screen_h, point),
+                None => Surface::Road,
+            },
+            dt,
+            // Elimination races go on until the last car is knocked out, and
+            // a checkpoint rush until the clock runs down
+            if self.knockout.is_some() || !self.rush.is_empty() {
+                u32::MAX
+            } else {
+                data.laps
+            },
+            data.ghost_cars,
+        );
 
-        if data.race_started {
-            data.race_time += dt;
+        for (score, car) in self.drift.iter_mut().zip(&self.cars) {
+            score.update(car, dt);
         }
+        self.run_rush_clocks(data);
 
-        let (accel_rate, brake_rate, drag, max_speed, handling) = match data.selected_car {
-            None | Some(CarChoice::Car1) => (200.0, 300.0, 4.0, 400.0, 120.0),
-            Some(CarChoice::Car2) => (100.0, 100.0, 1.0, 1000.0, 240.0),
-            Some(CarChoice::Car3) => (500.0, 500.0, 8.0, 200.0, 300.0),
-            Some(CarChoice::Car4) => (600.0, 200.0, 4.0, 400.0, 60.0),
-        };
-
-        let accel = if self.player_acceleration > 0.0 {
-            accel_rate
-        } else if self.player_acceleration < 0.0 {
-            -brake_rate
-        } else {
-            0.0
-        };
-
-        self.player_speed += accel * dt;
-        if accel == 0.0 {
-            self.player_speed -= self.player_speed * drag * dt;
+        if let Some(sounds) = &data.sounds {
+            for (i, car) in self.cars[..self.players].iter().enumerate() {
+                sounds.drive(i, car, &data.volumes);
+            }
+            if hits > 0 {
+                sounds.play(Effect::Impact, &data.volumes);
+            }
         }
 
-        self.player_speed = self.player_speed.clamp(-0.5 * max_speed, max_speed);
+        let finishers: Vec<usize> = (0..self.players)
+            .filter(|&i| self.cars[i].progress.finished && !was_finished[i])
+            .collect();
+        if !finishers.is_empty() {
+            self.save_records(data, &finishers);
+            if !self.drift.is_empty() {
+                self.save_drift_scores(data, &finishers);
+            }
+        }
 
-        let steering = self.player_rot_vel * handling;
-        let speed_factor = (self.player_speed.abs() / max_speed).clamp(0.4, 1.0);
+        if !self.targets.is_empty() {
+            self.award_medals(data);
+        }
 
-        self.player_direction = (self.player_direction + steering * speed_factor * dt) % 360.0;
+        if !data.race_started && self.cars.iter().any(|car| car.race_started) {
+            data.race_started = true;
+        }
 
-        let rad = self.player_direction.to_radians();
-        let forward = Vector2::new(rad.cos(), rad.sin());
+        if data.race_started && !self.race_over() {
+            data.race_time += dt;
+            self.run_knockouts(dt, data);
+        }
 
-        self.player_position += forward * self.player_speed * dt;
+        if data.mode == GameMode::Championship
+            && !self.test_drive
+            && !self.cup_scored
+            && self.race_over()
+        {
+            self.score_cup(data);
+        }
 
         SceneSwitch::None
     }
@@ -234,97 +684,219 @@ impl Scene for GameScene {
     fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
         d.clear_background(Color::BLACK);
 
-        let minutes = (data.race_time / 60.0).floor() as i32;
-        let seconds = (data.race_time % 60.0) as i32;
-        let milliseconds = ((data.race_time * 1000.0) % 1000.0) as i32;
-
-        let timer_text = format!("{:02}:{:02}.{:03}", minutes, seconds, milliseconds);
-
-        if let Some(track) = &self.track_texture {
-            let tex_w = track.width as f32;
-            let tex_h = track.height as f32;
-            let win_w = data.screen_width as f32;
-            let win_h = data.screen_height as f32;
-            let scale = (win_w / tex_w).max(win_h / tex_h);
+        // The race is laid out on the virtual screen, fitted into the window
+        let (scale, offset) = letterbox(data);
 
+        if let Some(target) = &self.race_view {
+            let size = Vector2::new(target.texture.width as f32, target.texture.height as f32);
             d.draw_texture_pro(
-                track,
-                Rectangle::new(0.0, 0.0, tex_w, tex_h),
-                Rectangle {
-                    x: (win_w - tex_w * scale) / 2.0,
-                    y: (win_h - tex_h * scale) / 2.0,
-                    width: tex_w * scale,
-                    height: tex_h * scale,
-                },
+                target,
+                // Render textures are stored upside down
+                Rectangle::new(0.0, 0.0, size.x, -size.y),
+                Rectangle::new(
+                    offset.x,
+                    offset.y,
+                    VIRTUAL_WIDTH as f32 * scale,
+                    VIRTUAL_HEIGHT as f32 * scale,
+                ),
                 Vector2::zero(),
                 0.0,
                 Color::WHITE,
             );
         }
 
-        let sprites = self.car_sprites.as_ref().unwrap();
-        let tex = if self.player_rot_vel > 0.2 {
-            &sprites.right
-        } else if self.player_rot_vel < -0.2 {
-            &sprites.left
-        } else {
-            &sprites.straight
-        };
+        let count = self.players;
+        let field = self.cars.len();
 
-        let dest = Rectangle {
-            x: self.player_position.x,
-            y: self.player_position.y,
-            width: tex.width as f32 * CAR_SCALE,
-            height: tex.height as f32 * CAR_SCALE,
-        };
+        // Everything over the track is drawn in virtual screen space too
+        let mut v = d.begin_mode2D(Camera2D {
+            offset,
+            target: Vector2::zero(),
+            rotation: 0.0,
+            zoom: scale,
+        });
 
-        d.draw_texture_pro(
-            tex,
-            Rectangle::new(0.0, 0.0, tex.width as f32, tex.height as f32),
-            dest,
-            Vector2 {
-                x: dest.width / 2.0,
-                y: dest.height / 2.0,
-            },
-            self.player_direction + CAR_SPRITE_ROT_OFFSET,
-            Color::WHITE,
-        );
+        let places = race_places(&self.cars, &self.checkpoints);
+        for (i, car) in self.cars[..count].iter().enumerate() {
+            let label = if count > 1 {
+                format!("P{} - {}", i + 1, car_name(&car.choice))
+            } else {
+                car_name(&car.choice).to_string()
+            };
+            let info = HudInfo {
+                label: &label,
+                place: (field > 1).then_some((places[i], field)),
+                laps: (self.knockout.is_none() && self.rush.is_empty()).then_some(data.laps),
+                medal: self.medal_pace(car, data),
+                knockout: self.knockout_status(i, places[i]),
+                rush: self.rush.get(i).map(|clock| RushStatus {
+                    time_left: clock.time_left(car),
+                    gates: clock.gates,
+                    best: self
+                        .records_key
+                        .as_ref()
+                        .and_then(|key| data.records.best_rush(key)),
+                    bonus: clock.last_bonus,
+                    out: car.retired,
+                }),
+                drift: self.drift.get(i).map(|score| DriftStatus {
+                    score,
+                    board: self
+                        .records_key
+                        .as_ref()
+                        .map_or(&[], |key| data.records.drift_board(key)),
+                }),
+            };
+            draw_hud(&mut v, Self::viewport(i, count), car, &info);
+        }
 
-        d.draw_text(
-            &timer_text,
-            10, // X (top-left)
-            10, // Y
-            30, // font size
-            Color::WHITE,
-        );
+        // Borders between viewports
+        if count > 1 {
+            let w = VIRTUAL_WIDTH;
+            let h = VIRTUAL_HEIGHT;
+            v.draw_line_ex(
+                Vector2::new(0.0, h as f32 / 2.0),
+                Vector2::new(w as f32, h as f32 / 2.0),
+                4.0,
+                Color::BLACK,
+            );
+            if count > 2 {
+                v.draw_line_ex(
+                    Vector2::new(w as f32 / 2.0, 0.0),
+                    Vector2::new(w as f32 / 2.0, h as f32),
+                    4.0,
+                    Color::BLACK,
+                );
+            }
+        }
+
+        if self.race_over() {
+            let text = if self.test_drive {
+                "Press Enter to return to the editor"
+            } else if data.mode == GameMode::Championship {
+                "Press Enter to see the standings"
+            } else if !data.mode.has_opponents() {
+                "Press R to go again, or Enter to return to the menu"
+            } else {
+                "Press Enter to return to the menu"
+            };
+            let size = 25;
+            let width = v.measure_text(text, size);
+            v.draw_text(
+                text,
+                (VIRTUAL_WIDTH - width) / 2,
+                VIRTUAL_HEIGHT - size - 20,
+                size,
+                Color::WHITE,
+            );
+        }
+    }
+
+    // The track and cars go into a texture the size of the letterboxed
+    // window times the render scale, which draw then stretches to fit
+    fn draw_offscreen(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        data: &mut GameData,
+    ) {
+        let (scale, _) = letterbox(data);
+        let scale = scale * data.display.render_scale;
+        let width = (VIRTUAL_WIDTH as f32 * scale).round().max(1.0) as i32;
+        let height = (VIRTUAL_HEIGHT as f32 * scale).round().max(1.0) as i32;
+
+        let stale = self
+            .race_view
+            .as_ref()
+            .is_none_or(|target| (target.texture.width, target.texture.height) != (width, height));
+        if stale {
+            // Without one the race view is left black
+            self.race_view = rl
+                .load_render_texture(thread, width as u32, height as u32)
+                .ok();
+            if let Some(target) = &self.race_view {
+                target.set_texture_filter(thread, TextureFilter::TEXTURE_FILTER_BILINEAR);
+            }
+        }
 
-        let car_name = match data.selected_car {
-            None | Some(CarChoice::Car1) => "Default car",
-            Some(CarChoice::Car2) => "High inertia car",
-            Some(CarChoice::Car3) => "Responsive car",
-            Some(CarChoice::Car4) => "Stubborn car",
+        let Some(mut target) = self.race_view.take() else {
+            return;
         };
 
-        let font_size = 25;
-        let padding = 10;
+        {
+            let mut t = rl.begin_texture_mode(thread, &mut target);
+            t.clear_background(Color::BLACK);
+
+            for (view, camera, focus) in self.views() {
+                let mut s = t.begin_scissor_mode(
+                    (view.x * scale) as i32,
+                    (view.y * scale) as i32,
+                    (view.width * scale) as i32,
+                    (view.height * scale) as i32,
+                );
+                let mut m = s.begin_mode2D(Camera2D {
+                    offset: camera.offset * scale,
+                    zoom: camera.zoom * scale,
+                    ..camera
+                });
+                self.draw_world(&mut m, focus, data);
+            }
+        }
 
-        // Measure text width so it aligns to bottom right
-        let text_width = d.measure_text(car_name, font_size);
+        self.race_view = Some(target);
+    }
 
-        let x = data.screen_width - text_width - padding;
-        let y = data.screen_height - font_size - padding;
+    fn on_exit(&mut self, _: &mut RaylibHandle, data: &mut GameData, _: &RaylibThread) {
+        // Best laps still count from a race left early
+        let all: Vec<usize> = (0..self.players).collect();
+        self.save_records(data, &all);
 
-        d.draw_text(car_name, x, y, font_size, Color::WHITE);
+        self.race_view = None;
+        if let Some(sounds) = &data.sounds {
+            sounds.stop_cars();
+        }
+        if let Some(music) = &mut data.music {
+            music.play(Playlist::Menu);
+        }
     }
 
-    fn on_exit(&mut self, _: &mut RaylibHandle, _: &mut GameData, _: &RaylibThread) {}
-}
+    // Swap in edited assets without restarting the race
+    fn on_assets_changed(
+        &mut self,
+        rl: &mut RaylibHandle,
+        data: &mut GameData,
+        thread: &RaylibThread,
+        changed: &[String],
+    ) {
+        let was_changed = |path: &str| changed.iter().any(|c| c == path);
+
+        // Cars stay where they are, only the road and gates move
+        let track_changed = |track: &TrackChoice| match track {
+            TrackChoice::Custom(slot) => {
+                was_changed(&track_path(track)) || was_changed(&custom_manifest_path(*slot))
+            }
+            _ => was_changed(&track_path(track)),
+        };
 
-fn track_path(track: &TrackChoice) -> &'static str {
-    match track {
-        TrackChoice::Track1 => "Assets/track1.png",
-        TrackChoice::Track2 => "Assets/track2.png",
-        TrackChoice::Track3 => "Assets/track3.png",
-        TrackChoice::Track4 => "Assets/track4.png",
+        if !self.test_drive
+            && let Some(track) = data.selected_track
+            && track_changed(&track)
+        {
+            self.load_track(rl, thread, &track, data);
+            println!("Reloaded {}", track_path(&track));
+        }
+
+        for car in &mut self.cars {
+            if car_sprite_paths(&car.choice)
+                .iter()
+                .any(|path| was_changed(path))
+            {
+                car.sprites = Some(CarSprites::load(rl, thread, &mut data.assets, &car.choice));
+            }
+
+            if was_changed(CAR_DATA_PATH) {
+                car.stats = data.car_table.stats(&car.choice);
+            }
+        }
     }
-}
\ No newline at end of file
+}
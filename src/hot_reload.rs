@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+// Checking every frame would mean a directory walk each frame
+const POLL_INTERVAL: f32 = 0.5;
+
+// -------- Dev mode: notice edits to files under a directory --------
+// Paths are reported the way the game refers to them, e.g.
+// "Assets/track1.png", so they can be compared with load paths directly.
+pub struct AssetWatcher {
+    root: String,
+    // Files the game saves itself, which aren't edits to reload
+    ignored: Vec<String>,
+    modified: HashMap<String, SystemTime>,
+    timer: f32,
+}
+
+impl AssetWatcher {
+    pub fn new(root: &str, ignored: &[&str]) -> Self {
+        let mut watcher = Self {
+            root: root.to_string(),
+            ignored: ignored.iter().map(|path| path.to_string()).collect(),
+            modified: HashMap::new(),
+            timer: 0.0,
+        };
+        watcher.modified = watcher.scan();
+
+        println!(
+            "Watching {} for changes ({} files)",
+            root,
+            watcher.modified.len()
+        );
+        watcher
+    }
+
+    // Returns the files added or changed since the last poll
+    pub fn poll(&mut self, dt: f32) -> Vec<String> {
+        self.timer += dt;
+        if self.timer < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.timer = 0.0;
+
+        let current = self.scan();
+
+        let mut changed: Vec<String> = current
+            .iter()
+            .filter(|(path, time)| self.modified.get(*path) != Some(time))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.sort();
+
+        self.modified = current;
+        changed
+    }
+
+    fn scan(&self) -> HashMap<String, SystemTime> {
+        let mut files = HashMap::new();
+        scan(Path::new(&self.root), &mut files);
+        files.retain(|path, _| !self.ignored.contains(path));
+        files
+    }
+}
+
+fn scan(dir: &Path, files: &mut HashMap<String, SystemTime>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            scan(&path, files);
+        } else if let Ok(time) = entry.metadata().and_then(|meta| meta.modified()) {
+            files.insert(path.to_string_lossy().replace('\\', "/"), time);
+        }
+    }
+}
@@ -0,0 +1,786 @@
+use raylib::prelude::*;
+
+use crate::car::{Car, METERS_PER_PIXEL};
+use crate::display::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH};
+use crate::drift::DriftScore;
+use crate::medals::Medal;
+use crate::records::DRIFT_BOARD_SIZE;
+use crate::rush::Bonus;
+use crate::track::LapProgress;
+use crate::utils::{format_time, ordinal};
+
+type Hud<'a, 'b> = RaylibMode2D<'a, RaylibDrawHandle<'b>>;
+
+// Seconds a split delta and the lap banner stay up
+const SPLIT_SHOWN: f32 = 3.0;
+const BANNER_SHOWN: f32 = 2.5;
+const DRIFT_EVENT_SHOWN: f32 = 1.5;
+const BONUS_SHOWN: f32 = 1.5;
+// The rush clock turns red and pulses under this many seconds
+const CLOCK_LOW: f32 = 5.0;
+// Slip that fills the drift gauge
+const FULL_SLIP: f32 = 45.0;
+const KMH_PER_PIXEL: f32 = METERS_PER_PIXEL * 3.6;
+// The dial sweeps clockwise from bottom left round to bottom right
+const DIAL_START: f32 = 135.0;
+const DIAL_SWEEP: f32 = 270.0;
+
+const PANEL_COLOR: Color = Color::new(0, 0, 0, 150);
+const FASTER_COLOR: Color = Color::LIME;
+const SLOWER_COLOR: Color = Color::RED;
+
+// What a player's HUD shows beyond their car
+pub struct HudInfo<'a> {
+    // Car name, with the player number in a split screen
+    pub label: &'a str,
+    // Place and number of cars, None when racing alone
+    pub place: Option<(usize, usize)>,
+    // None when the race runs until the last car is knocked out
+    pub laps: Option<u32>,
+    // Time trials only, and only on tracks with medal times
+    pub medal: Option<MedalPace>,
+    // Elimination races only
+    pub knockout: Option<KnockoutStatus>,
+    // Checkpoint rushes only
+    pub rush: Option<RushStatus>,
+    // Drift challenges only
+    pub drift: Option<DriftStatus<'a>>,
+}
+
+pub struct MedalPace {
+    // The medal being chased and the best one held
+    pub target: Medal,
+    pub held: Option<Medal>,
+    // Seconds ahead (negative) or behind the target's pace this lap
+    pub delta: f32,
+}
+
+pub struct KnockoutStatus {
+    // Last of the cars still racing, so next to go
+    pub in_danger: bool,
+    // Seconds to the next knockout when they're timed
+    pub next_in: Option<f32>,
+    pub out: bool,
+    // The last car left
+    pub won: bool,
+}
+
+pub struct RushStatus {
+    pub time_left: f32,
+    // Checkpoints reached this run, and the track's most
+    pub gates: usize,
+    pub best: Option<u32>,
+    pub bonus: Option<Bonus>,
+    // The clock has run out
+    pub out: bool,
+}
+
+pub struct DriftStatus<'a> {
+    pub score: &'a DriftScore,
+    // The track's leaderboard, best first
+    pub board: &'a [f32],
+}
+
+// -------- Everything drawn over one player's view --------
+// `view` is in virtual screen space, and the HUD shrinks with it so
+// split screens don't fill up with text.
+pub fn draw_hud(d: &mut Hud, view: Rectangle, car: &Car, info: &HudInfo) {
+    let scale = (view.width / VIRTUAL_WIDTH as f32)
+        .min(view.height / VIRTUAL_HEIGHT as f32)
+        .sqrt();
+    let at = |x: f32, y: f32| Vector2::new(view.x + x * scale, view.y + y * scale);
+    let size = |font: f32| (font * scale) as i32;
+
+    draw_lap_panel(d, at(10.0, 10.0), scale, car, info.laps);
+    if let Some(pace) = &info.medal {
+        draw_medal_panel(d, at(10.0, 130.0), scale, pace);
+    }
+    if let Some(rush) = &info.rush {
+        draw_rush_panel(d, at(10.0, 130.0), scale, car, rush);
+    }
+    if let Some(drift) = &info.drift {
+        draw_drift_panel(d, at(10.0, 130.0), scale, car, drift);
+        draw_drift_event(d, view, scale, car, drift.score);
+        if car.progress.finished {
+            draw_drift_board(d, view, scale, drift);
+        }
+    }
+
+    if let Some((place, count)) = info.place {
+        let place_text = ordinal(place);
+        let count_text = format!("/{}", count);
+        let place_width = d.measure_text(&place_text, size(50.0));
+        let count_width = d.measure_text(&count_text, size(25.0));
+
+        let right = view.x + view.width - 15.0 * scale;
+        let top = view.y + 10.0 * scale;
+        let x = right - (place_width + count_width) as f32;
+        d.draw_text(&place_text, x as i32, top as i32, size(50.0), Color::WHITE);
+        d.draw_text(
+            &count_text,
+            x as i32 + place_width,
+            (top + 22.0 * scale) as i32,
+            size(25.0),
+            Color::LIGHTGRAY,
+        );
+    }
+
+    draw_split(d, view, scale, car);
+    // Being knocked out, winning or running out of time takes the
+    // banner's place
+    let knocked = info.knockout.as_ref();
+    let timed_out = info.rush.as_ref().is_some_and(|rush| rush.out);
+    if !knocked.is_some_and(|status| status.out || status.won) && !timed_out {
+        draw_banner(d, view, scale, car, info);
+    }
+    if let Some(status) = knocked {
+        draw_knockout(d, view, scale, car, info.place, status);
+    }
+    if let Some(rush) = &info.rush {
+        draw_rush_events(d, view, scale, car, rush);
+    }
+
+    let radius = 55.0 * scale;
+    let center = Vector2::new(
+        view.x + 15.0 * scale + radius,
+        view.y + view.height - 15.0 * scale - radius,
+    );
+    draw_speedometer(d, center, radius, car);
+
+    // Bottom right, clear of the speedometer
+    let font_size = size(25.0);
+    let text_width = d.measure_text(info.label, font_size);
+    d.draw_text(
+        info.label,
+        (view.x + view.width - 10.0 * scale) as i32 - text_width,
+        (view.y + view.height - 10.0 * scale) as i32 - font_size,
+        font_size,
+        Color::WHITE,
+    );
+}
+
+// Lap count, this lap's time, the race so far and the best lap
+fn draw_lap_panel(d: &mut Hud, top_left: Vector2, scale: f32, car: &Car, laps: Option<u32>) {
+    let progress = &car.progress;
+    let size = |font: f32| (font * scale) as i32;
+
+    d.draw_rectangle_rounded(
+        Rectangle::new(top_left.x, top_left.y, 210.0 * scale, 112.0 * scale),
+        0.2,
+        8,
+        PANEL_COLOR,
+    );
+
+    let x = (top_left.x + 10.0 * scale) as i32;
+    let y = |offset: f32| (top_left.y + offset * scale) as i32;
+
+    let lap_text = match laps {
+        _ if progress.finished => "Finished".to_string(),
+        Some(laps) => format!("Lap {}/{}", progress.lap, laps),
+        None => format!("Lap {}", progress.lap),
+    };
+    d.draw_text(&lap_text, x, y(8.0), size(30.0), Color::WHITE);
+
+    // Finished cars keep showing their last lap
+    let lap_time = match (progress.finished, progress.last_lap) {
+        (true, Some(last)) => last,
+        _ => car.race_time - progress.lap_start,
+    };
+    d.draw_text(&format_time(lap_time), x, y(42.0), size(25.0), Color::WHITE);
+
+    let total = format!("Total {}", format_time(car.race_time));
+    d.draw_text(&total, x, y(72.0), size(16.0), Color::LIGHTGRAY);
+
+    let best = match progress.best_lap {
+        Some(best) => format!("Best  {}", format_time(best)),
+        None => "Best  --:--.---".to_string(),
+    };
+    d.draw_text(&best, x, y(92.0), size(16.0), Color::LIGHTGRAY);
+}
+
+// The running delta to the medal being chased, and the best one held
+fn draw_medal_panel(d: &mut Hud, top_left: Vector2, scale: f32, pace: &MedalPace) {
+    let size = |font: f32| (font * scale) as i32;
+
+    d.draw_rectangle_rounded(
+        Rectangle::new(top_left.x, top_left.y, 210.0 * scale, 62.0 * scale),
+        0.2,
+        8,
+        PANEL_COLOR,
+    );
+
+    let x = (top_left.x + 10.0 * scale) as i32;
+    let y = |offset: f32| (top_left.y + offset * scale) as i32;
+
+    let target = format!("vs {}", pace.target.name());
+    d.draw_text(&target, x, y(8.0), size(22.0), pace.target.color());
+
+    let target_width = d.measure_text(&target, size(22.0));
+    let color = if pace.delta <= 0.0 {
+        FASTER_COLOR
+    } else {
+        SLOWER_COLOR
+    };
+    d.draw_text(
+        &format_delta(pace.delta),
+        x + target_width + size(12.0),
+        y(8.0),
+        size(22.0),
+        color,
+    );
+
+    let (held, color) = match pace.held {
+        Some(medal) => (format!("Held  {}", medal.name()), medal.color()),
+        None => ("Held  none".to_string(), Color::LIGHTGRAY),
+    };
+    d.draw_text(&held, x, y(38.0), size(16.0), color);
+}
+
+// Points banked, the drift under way with its multiplier, and a gauge of
+// how far the car is sliding
+fn draw_drift_panel(d: &mut Hud, top_left: Vector2, scale: f32, car: &Car, drift: &DriftStatus) {
+    let score = drift.score;
+    let size = |font: f32| (font * scale) as i32;
+
+    d.draw_rectangle_rounded(
+        Rectangle::new(top_left.x, top_left.y, 210.0 * scale, 112.0 * scale),
+        0.2,
+        8,
+        PANEL_COLOR,
+    );
+
+    let x = (top_left.x + 10.0 * scale) as i32;
+    let y = |offset: f32| (top_left.y + offset * scale) as i32;
+
+    let total = format!("{}", score.total.round() as i32);
+    d.draw_text(&total, x, y(8.0), size(30.0), Color::WHITE);
+
+    let combo = format!("x{}", score.multiplier);
+    let combo_width = d.measure_text(&combo, size(30.0));
+    let combo_color = if score.multiplier > 1 {
+        Color::ORANGE
+    } else {
+        Color::LIGHTGRAY
+    };
+    d.draw_text(
+        &combo,
+        (top_left.x + 200.0 * scale) as i32 - combo_width,
+        y(8.0),
+        size(30.0),
+        combo_color,
+    );
+
+    if score.drifting {
+        let pending = format!("+{}", score.pending().round() as i32);
+        d.draw_text(&pending, x, y(42.0), size(22.0), Color::GOLD);
+    }
+
+    // Lights up once the slide is enough to score
+    let gauge = Rectangle::new(
+        top_left.x + 10.0 * scale,
+        top_left.y + 70.0 * scale,
+        190.0 * scale,
+        8.0 * scale,
+    );
+    let share = (car.slip_angle() / FULL_SLIP).min(1.0);
+    d.draw_rectangle_rec(gauge, Color::DARKGRAY);
+    d.draw_rectangle_rec(
+        Rectangle::new(gauge.x, gauge.y, gauge.width * share, gauge.height),
+        if score.drifting {
+            Color::ORANGE
+        } else {
+            Color::LIGHTGRAY
+        },
+    );
+
+    let best = match drift.board.first() {
+        Some(best) => format!("Best  {}", best.round() as i32),
+        None => "Best  -".to_string(),
+    };
+    d.draw_text(&best, x, y(88.0), size(16.0), Color::LIGHTGRAY);
+}
+
+// The track's top scores under the finish banner, this run's picked out
+fn draw_drift_board(d: &mut Hud, view: Rectangle, scale: f32, drift: &DriftStatus) {
+    let size = |font: f32| (font * scale) as i32;
+    let row_height = 26.0 * scale;
+    let width = 240.0 * scale;
+    let height = 44.0 * scale + DRIFT_BOARD_SIZE as f32 * row_height;
+    let left = view.x + (view.width - width) / 2.0;
+    let top = view.y + view.height / 2.0 + 10.0 * scale;
+
+    d.draw_rectangle_rounded(
+        Rectangle::new(left, top, width, height),
+        0.1,
+        8,
+        PANEL_COLOR,
+    );
+
+    let title = "Drift Leaderboard";
+    let title_width = d.measure_text(title, size(20.0));
+    d.draw_text(
+        title,
+        (left + (width - title_width as f32) / 2.0) as i32,
+        (top + 10.0 * scale) as i32,
+        size(20.0),
+        Color::WHITE,
+    );
+
+    for row in 0..DRIFT_BOARD_SIZE {
+        let y = (top + 40.0 * scale + row as f32 * row_height) as i32;
+        let color = if drift.score.board_place == Some(row + 1) {
+            Color::GOLD
+        } else {
+            Color::LIGHTGRAY
+        };
+        let score = drift
+            .board
+            .get(row)
+            .map_or("-".to_string(), |score| (score.round() as i32).to_string());
+
+        d.draw_text(
+            &format!("{}.", row + 1),
+            (left + 20.0 * scale) as i32,
+            y,
+            size(20.0),
+            color,
+        );
+        let score_width = d.measure_text(&score, size(20.0));
+        d.draw_text(
+            &score,
+            (left + width - 20.0 * scale) as i32 - score_width,
+            y,
+            size(20.0),
+            color,
+        );
+    }
+}
+
+// Points banked or a lost combo, fading out under the split
+fn draw_drift_event(d: &mut Hud, view: Rectangle, scale: f32, car: &Car, score: &DriftScore) {
+    // The clock stops at the flag, so a last event would never fade
+    let Some(event) = &score.last_event else {
+        return;
+    };
+    if car.progress.finished {
+        return;
+    }
+    let age = car.race_time - event.at;
+    if !(0.0..DRIFT_EVENT_SHOWN).contains(&age) {
+        return;
+    }
+
+    let color = if event.good {
+        FASTER_COLOR
+    } else {
+        SLOWER_COLOR
+    };
+    draw_popup(d, view, scale, &event.text, color, age / DRIFT_EVENT_SHOWN);
+}
+
+// Seconds left, counting down, and the checkpoints reached against the best
+fn draw_rush_panel(d: &mut Hud, top_left: Vector2, scale: f32, car: &Car, rush: &RushStatus) {
+    let size = |font: f32| (font * scale) as i32;
+
+    d.draw_rectangle_rounded(
+        Rectangle::new(top_left.x, top_left.y, 210.0 * scale, 92.0 * scale),
+        0.2,
+        8,
+        PANEL_COLOR,
+    );
+
+    let x = (top_left.x + 10.0 * scale) as i32;
+    let y = |offset: f32| (top_left.y + offset * scale) as i32;
+
+    let color = if rush.time_left < CLOCK_LOW && !rush.out {
+        let pulse = 0.6 + 0.4 * (car.race_time * 8.0).sin().abs();
+        SLOWER_COLOR.alpha(pulse)
+    } else {
+        Color::WHITE
+    };
+    let clock = format!("{:.1}", rush.time_left);
+    d.draw_text(&clock, x, y(6.0), size(40.0), color);
+
+    let gates = format!("Checkpoints {}", rush.gates);
+    d.draw_text(&gates, x, y(50.0), size(16.0), Color::LIGHTGRAY);
+
+    let best = match rush.best {
+        Some(best) => format!("Best  {}", best),
+        None => "Best  -".to_string(),
+    };
+    d.draw_text(&best, x, y(70.0), size(16.0), Color::LIGHTGRAY);
+}
+
+// Time added at the last gate, or the final score once the clock's out
+fn draw_rush_events(d: &mut Hud, view: Rectangle, scale: f32, car: &Car, rush: &RushStatus) {
+    if rush.out {
+        let best = rush.best.is_some_and(|best| best as usize == rush.gates) && rush.gates > 0;
+        let detail = format!("{} checkpoints reached", rush.gates);
+        draw_message(
+            d,
+            view,
+            scale,
+            ("Time Up!", Color::ORANGE),
+            (&detail, if best { Color::GOLD } else { Color::LIGHTGRAY }),
+        );
+        return;
+    }
+
+    let Some(bonus) = rush.bonus else {
+        return;
+    };
+    let age = car.race_time - bonus.at;
+    if (0.0..BONUS_SHOWN).contains(&age) {
+        let text = format!("+{:.1}s", bonus.seconds);
+        draw_popup(d, view, scale, &text, FASTER_COLOR, age / BONUS_SHOWN);
+    }
+}
+
+// A line of text under the split, rising and fading as `age` goes 0 to 1
+fn draw_popup(d: &mut Hud, view: Rectangle, scale: f32, text: &str, color: Color, age: f32) {
+    let font_size = (30.0 * scale) as i32;
+    let width = d.measure_text(text, font_size);
+    d.draw_text(
+        text,
+        (view.x + (view.width - width as f32) / 2.0) as i32,
+        (view.y + (80.0 - 30.0 * age) * scale) as i32,
+        font_size,
+        color.alpha(1.0 - age),
+    );
+}
+
+// Time at the last gate, against the best lap's time there
+fn draw_split(d: &mut Hud, view: Rectangle, scale: f32, car: &Car) {
+    let Some(split) = car.progress.last_split else {
+        return;
+    };
+    if car.progress.finished || car.race_time - split.at > SPLIT_SHOWN {
+        return;
+    }
+
+    let (text, color) = match split.delta {
+        Some(delta) => (
+            format_delta(delta),
+            if delta <= 0.0 {
+                FASTER_COLOR
+            } else {
+                SLOWER_COLOR
+            },
+        ),
+        None => (format_time(split.lap_time), Color::WHITE),
+    };
+
+    let font_size = (32.0 * scale) as i32;
+    let width = d.measure_text(&text, font_size);
+    let center_x = view.x + view.width / 2.0;
+    let top = view.y + 12.0 * scale;
+
+    d.draw_rectangle_rounded(
+        Rectangle::new(
+            center_x - width as f32 / 2.0 - 12.0 * scale,
+            top,
+            width as f32 + 24.0 * scale,
+            font_size as f32 + 12.0 * scale,
+        ),
+        0.3,
+        8,
+        PANEL_COLOR,
+    );
+    d.draw_text(
+        &text,
+        (center_x - width as f32 / 2.0) as i32,
+        (top + 6.0 * scale) as i32,
+        font_size,
+        color,
+    );
+}
+
+// Shown for a moment after each lap, and for good once finished
+fn draw_banner(d: &mut Hud, view: Rectangle, scale: f32, car: &Car, info: &HudInfo) {
+    let progress = &car.progress;
+    let Some(last_lap) = progress.last_lap else {
+        return;
+    };
+
+    let (title, color) = if progress.finished {
+        match info.place {
+            Some((place, _)) => (format!("Finished {}!", ordinal(place)), Color::GOLD),
+            None => ("Finished!".to_string(), Color::GOLD),
+        }
+    } else if car.race_time - progress.lap_start < BANNER_SHOWN {
+        if Some(progress.lap) == info.laps {
+            ("Final Lap".to_string(), Color::ORANGE)
+        } else {
+            (format!("Lap {}", progress.lap), Color::WHITE)
+        }
+    } else {
+        return;
+    };
+
+    let best = beat_best_lap(progress);
+    let detail = if best {
+        format!("Best lap {}", format_time(last_lap))
+    } else {
+        format!("Lap time {}", format_time(last_lap))
+    };
+    let detail_color = if best { Color::GOLD } else { Color::LIGHTGRAY };
+
+    draw_message(d, view, scale, (&title, color), (&detail, detail_color));
+}
+
+// Only once there was an earlier lap to beat
+fn beat_best_lap(progress: &LapProgress) -> bool {
+    progress.laps_done() > 1
+        && progress.last_lap.is_some()
+        && progress.last_lap == progress.best_lap
+}
+
+// Out or won for good, otherwise the danger warning and the next
+// knockout's countdown
+fn draw_knockout(
+    d: &mut Hud,
+    view: Rectangle,
+    scale: f32,
+    car: &Car,
+    place: Option<(usize, usize)>,
+    status: &KnockoutStatus,
+) {
+    if status.won {
+        draw_message(
+            d,
+            view,
+            scale,
+            ("Last Car Standing!", Color::GOLD),
+            ("You win", Color::LIGHTGRAY),
+        );
+        return;
+    }
+    if status.out {
+        let detail = match place {
+            Some((place, _)) => format!("Finished {}", ordinal(place)),
+            None => "Out of the race".to_string(),
+        };
+        draw_message(
+            d,
+            view,
+            scale,
+            ("Eliminated", Color::RED),
+            (&detail, Color::LIGHTGRAY),
+        );
+        return;
+    }
+
+    let center_x = view.x + view.width / 2.0;
+
+    if let Some(next_in) = status.next_in {
+        let text = format!("Next out in {}s", next_in.ceil() as i32);
+        let font_size = (20.0 * scale) as i32;
+        let width = d.measure_text(&text, font_size);
+        d.draw_text(
+            &text,
+            (center_x - width as f32 / 2.0) as i32,
+            (view.y + view.height - 40.0 * scale) as i32,
+            font_size,
+            Color::WHITE,
+        );
+    }
+
+    if status.in_danger {
+        // Pulses so it's caught out of the corner of an eye
+        let pulse = 0.6 + 0.4 * (car.race_time * 8.0).sin().abs();
+        let text = "DANGER ZONE";
+        let font_size = (36.0 * scale) as i32;
+        let width = d.measure_text(text, font_size);
+        let top = view.y + 70.0 * scale;
+
+        d.draw_rectangle_rounded(
+            Rectangle::new(
+                center_x - width as f32 / 2.0 - 14.0 * scale,
+                top,
+                width as f32 + 28.0 * scale,
+                font_size as f32 + 12.0 * scale,
+            ),
+            0.3,
+            8,
+            SLOWER_COLOR.alpha(0.35 * pulse),
+        );
+        d.draw_text(
+            text,
+            (center_x - width as f32 / 2.0) as i32,
+            (top + 6.0 * scale) as i32,
+            font_size,
+            SLOWER_COLOR.alpha(pulse),
+        );
+    }
+}
+
+// A title and a line under it in a panel above the middle of the view
+fn draw_message(
+    d: &mut Hud,
+    view: Rectangle,
+    scale: f32,
+    (title, color): (&str, Color),
+    (detail, detail_color): (&str, Color),
+) {
+    let title_size = (40.0 * scale) as i32;
+    let detail_size = (22.0 * scale) as i32;
+    let center = Vector2::new(
+        view.x + view.width / 2.0,
+        view.y + view.height / 2.0 - 60.0 * scale,
+    );
+
+    let title_width = d.measure_text(title, title_size);
+    let detail_width = d.measure_text(detail, detail_size);
+    let width = title_width.max(detail_width) as f32 + 40.0 * scale;
+    let height = (title_size + detail_size) as f32 + 30.0 * scale;
+
+    d.draw_rectangle_rounded(
+        Rectangle::new(
+            center.x - width / 2.0,
+            center.y - height / 2.0,
+            width,
+            height,
+        ),
+        0.2,
+        8,
+        PANEL_COLOR,
+    );
+
+    let top = center.y - height / 2.0 + 10.0 * scale;
+    d.draw_text(
+        title,
+        (center.x - title_width as f32 / 2.0) as i32,
+        top as i32,
+        title_size,
+        color,
+    );
+    d.draw_text(
+        detail,
+        (center.x - detail_width as f32 / 2.0) as i32,
+        (top + title_size as f32 + 10.0 * scale) as i32,
+        detail_size,
+        detail_color,
+    );
+}
+
+// Dial filled up to the car's speed, out of its own top speed
+fn draw_speedometer(d: &mut Hud, center: Vector2, radius: f32, car: &Car) {
+    let speed = car.speed.abs();
+    let share = (speed / car.stats.max_speed).clamp(0.0, 1.0);
+    let end = DIAL_START + DIAL_SWEEP * share;
+    let segments = 36;
+
+    d.draw_circle_v(center, radius, PANEL_COLOR);
+    d.draw_ring(
+        center,
+        radius * 0.75,
+        radius * 0.9,
+        DIAL_START,
+        DIAL_START + DIAL_SWEEP,
+        segments,
+        Color::DARKGRAY,
+    );
+    d.draw_ring(
+        center,
+        radius * 0.75,
+        radius * 0.9,
+        DIAL_START,
+        end,
+        segments,
+        Color::SKYBLUE.lerp(Color::ORANGERED, share),
+    );
+
+    let angle = end.to_radians();
+    let tip = center + Vector2::new(angle.cos(), angle.sin()) * radius * 0.85;
+    d.draw_line_ex(center, tip, (radius * 0.05).max(1.0), Color::RED);
+    d.draw_circle_v(center, radius * 0.08, Color::RED);
+
+    let number = format!("{}", (speed * KMH_PER_PIXEL).round() as i32);
+    let number_size = (radius * 0.45) as i32;
+    let unit_size = (radius * 0.22) as i32;
+    let number_width = d.measure_text(&number, number_size);
+    let unit_width = d.measure_text("km/h", unit_size);
+
+    d.draw_text(
+        &number,
+        (center.x - number_width as f32 / 2.0) as i32,
+        (center.y + radius * 0.2) as i32,
+        number_size,
+        Color::WHITE,
+    );
+    d.draw_text(
+        "km/h",
+        (center.x - unit_width as f32 / 2.0) as i32,
+        (center.y + radius * 0.62) as i32,
+        unit_size,
+        Color::LIGHTGRAY,
+    );
+}
+
+fn format_delta(delta: f32) -> String {
+    let sign = if delta <= 0.0 { '-' } else { '+' };
+    format!("{}{:.3}", sign, delta.abs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::track::Checkpoint;
+
+    // The start line at x = 0 and one gate further on
+    fn gates() -> Vec<Checkpoint> {
+        [0.0, 100.0]
+            .map(|x| Checkpoint {
+                start: Vector2::new(x, -10.0),
+                end: Vector2::new(x, 10.0),
+            })
+            .to_vec()
+    }
+
+    // Round the gates and over the line at `race_time`
+    fn drive_lap(progress: &mut LapProgress, race_time: f32, total_laps: u32) {
+        let gates = gates();
+        let across = |x: f32| (Vector2::new(x - 1.0, 0.0), Vector2::new(x + 1.0, 0.0));
+        for x in [100.0, 0.0] {
+            let (from, to) = across(x);
+            progress.advance(&gates, from, to, race_time, total_laps);
+        }
+    }
+
+    #[test]
+    fn a_one_lap_race_has_no_best_lap() {
+        let mut progress = LapProgress::new();
+        drive_lap(&mut progress, 10.0, 1);
+
+        assert!(progress.finished);
+        assert!(!beat_best_lap(&progress));
+    }
+
+    #[test]
+    fn the_first_lap_is_not_a_best_lap() {
+        let mut progress = LapProgress::new();
+        drive_lap(&mut progress, 10.0, 2);
+
+        assert_eq!(progress.laps_done(), 1);
+        assert!(!beat_best_lap(&progress));
+    }
+
+    #[test]
+    fn a_quicker_second_lap_is_a_best_lap() {
+        let mut progress = LapProgress::new();
+        drive_lap(&mut progress, 10.0, 2);
+        drive_lap(&mut progress, 19.0, 2);
+
+        assert!(progress.finished);
+        assert!(beat_best_lap(&progress));
+    }
+
+    #[test]
+    fn a_slower_second_lap_is_not() {
+        let mut progress = LapProgress::new();
+        drive_lap(&mut progress, 10.0, 2);
+        drive_lap(&mut progress, 21.0, 2);
+
+        assert!(!beat_best_lap(&progress));
+    }
+}
@@ -0,0 +1,133 @@
+use raylib::prelude::*;
+
+use crate::game_data::{ControlChoice, MAX_PLAYERS};
+
+const STICK_DEADZONE: f32 = 0.2;
+
+#[derive(Copy, Clone, Default)]
+pub struct CarInput {
+    pub acceleration: f32,
+    pub rot_vel: f32,
+}
+
+pub fn read_car_input(rl: &RaylibHandle, control: Option<ControlChoice>) -> CarInput {
+    use KeyboardKey::*;
+
+    match control {
+        Some(ControlChoice::Keyboard) | None => read_keys(
+            rl,
+            &[KEY_W, KEY_UP],
+            &[KEY_S, KEY_DOWN],
+            &[KEY_A, KEY_LEFT],
+            &[KEY_D, KEY_RIGHT],
+        ),
+        Some(ControlChoice::KeyboardLeft) => read_keys(rl, &[KEY_W], &[KEY_S], &[KEY_A], &[KEY_D]),
+        Some(ControlChoice::KeyboardRight) => {
+            read_keys(rl, &[KEY_UP], &[KEY_DOWN], &[KEY_LEFT], &[KEY_RIGHT])
+        }
+        Some(ControlChoice::Controller(pad)) => read_gamepad(rl, pad),
+    }
+}
+
+fn read_keys(
+    rl: &RaylibHandle,
+    accelerate: &[KeyboardKey],
+    brake: &[KeyboardKey],
+    left: &[KeyboardKey],
+    right: &[KeyboardKey],
+) -> CarInput {
+    let any_down = |keys: &[KeyboardKey]| keys.iter().any(|key| rl.is_key_down(*key));
+    let mut input = CarInput::default();
+
+    if any_down(accelerate) {
+        input.acceleration = 1.0;
+    } else if any_down(brake) {
+        input.acceleration = -1.0;
+    }
+
+    if any_down(right) {
+        input.rot_vel = 1.0;
+    } else if any_down(left) {
+        input.rot_vel = -1.0;
+    }
+
+    input
+}
+
+fn read_gamepad(rl: &RaylibHandle, pad: i32) -> CarInput {
+    let mut input = CarInput::default();
+
+    if !rl.is_gamepad_available(pad) {
+        return input;
+    }
+
+    // Triggers rest at -1.0 and go to 1.0 when fully pressed
+    let throttle = rl.get_gamepad_axis_movement(pad, GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER);
+    let brake = rl.get_gamepad_axis_movement(pad, GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER);
+
+    if throttle > 0.0
+        || rl.is_gamepad_button_down(pad, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)
+    {
+        input.acceleration = 1.0;
+    } else if brake > 0.0
+        || rl.is_gamepad_button_down(pad, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT)
+    {
+        input.acceleration = -1.0;
+    }
+
+    let stick = rl.get_gamepad_axis_movement(pad, GamepadAxis::GAMEPAD_AXIS_LEFT_X);
+    if stick.abs() > STICK_DEADZONE {
+        input.rot_vel = stick.clamp(-1.0, 1.0);
+    } else if rl.is_gamepad_button_down(pad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT) {
+        input.rot_vel = 1.0;
+    } else if rl.is_gamepad_button_down(pad, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT) {
+        input.rot_vel = -1.0;
+    }
+
+    input
+}
+
+// -------- One-shot presses used to move around menus --------
+#[derive(Copy, Clone, Default)]
+pub struct MenuInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool,
+    pub back: bool,
+}
+
+// Any keyboard or connected pad can drive the menus
+pub fn read_menu_input(rl: &RaylibHandle) -> MenuInput {
+    use GamepadButton::*;
+    use KeyboardKey::*;
+
+    let key = |keys: &[KeyboardKey]| keys.iter().any(|key| rl.is_key_pressed(*key));
+    let pad = |button: GamepadButton| {
+        (0..MAX_PLAYERS as i32)
+            .any(|pad| rl.is_gamepad_available(pad) && rl.is_gamepad_button_pressed(pad, button))
+    };
+
+    // Alt+Enter toggles fullscreen instead
+    let alt = rl.is_key_down(KEY_LEFT_ALT) || rl.is_key_down(KEY_RIGHT_ALT);
+    let enter = !alt && key(&[KEY_ENTER]);
+
+    MenuInput {
+        up: key(&[KEY_UP, KEY_W]) || pad(GAMEPAD_BUTTON_LEFT_FACE_UP),
+        down: key(&[KEY_DOWN, KEY_S]) || pad(GAMEPAD_BUTTON_LEFT_FACE_DOWN),
+        left: key(&[KEY_LEFT, KEY_A]) || pad(GAMEPAD_BUTTON_LEFT_FACE_LEFT),
+        right: key(&[KEY_RIGHT, KEY_D]) || pad(GAMEPAD_BUTTON_LEFT_FACE_RIGHT),
+        confirm: enter || key(&[KEY_SPACE]) || pad(GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+        back: key(&[KEY_ESCAPE, KEY_BACKSPACE]) || pad(GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+    }
+}
+
+// R or a pad's select button, starts a time trial over
+pub fn restart_pressed(rl: &RaylibHandle) -> bool {
+    rl.is_key_pressed(KeyboardKey::KEY_R)
+        || (0..MAX_PLAYERS as i32).any(|pad| {
+            rl.is_gamepad_available(pad)
+                && rl.is_gamepad_button_pressed(pad, GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT)
+        })
+}
@@ -0,0 +1,240 @@
+use crate::game_data::{CarChoice, ControlChoice, GameData, MAX_LAPS, TrackChoice};
+use crate::track_generator::TrackSeed;
+
+pub const USAGE: &str = "\
+Usage: racingGame [options]
+
+  --track <1-4>        start a race on this track, skipping the menus
+  --seed <n>           start a race on the random track with this seed
+  --car <1-4>          car for player 1 (default 1)
+  --control <scheme>   keyboard, wasd, arrows or pad<N> (e.g. pad1)
+  --laps <1-10>        number of laps (default 3)
+  --window <WxH>       window size, e.g. 1280x720
+  --fullscreen         start fullscreen
+  --mute               start with sound off
+  --dev                reload edited assets while running
+  --help               show this message";
+
+// -------- Options given on the command line --------
+pub struct LaunchOptions {
+    pub track: Option<TrackChoice>,
+    pub car: Option<CarChoice>,
+    pub control: Option<ControlChoice>,
+    pub laps: Option<u32>,
+    pub window_size: Option<(i32, i32)>,
+    pub fullscreen: bool,
+    pub mute: bool,
+    pub dev: bool,
+    pub help: bool,
+}
+
+impl LaunchOptions {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self {
+            track: None,
+            car: None,
+            control: None,
+            laps: None,
+            window_size: None,
+            fullscreen: false,
+            mute: false,
+            dev: false,
+            help: false,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+
+            match arg.as_str() {
+                "--track" => options.track = Some(parse_choice(&value()?, &TrackChoice::ALL)?),
+                "--seed" => options.track = Some(TrackChoice::Random(parse_seed(&value()?)?)),
+                "--car" => options.car = Some(parse_choice(&value()?, &CarChoice::ALL)?),
+                "--control" => options.control = Some(parse_control(&value()?)?),
+                "--laps" => options.laps = Some(parse_laps(&value()?)?),
+                "--window" => options.window_size = Some(parse_size(&value()?)?),
+                "--fullscreen" => options.fullscreen = true,
+                "--mute" => options.mute = true,
+                "--dev" => options.dev = true,
+                "--help" | "-h" => options.help = true,
+                _ => return Err(format!("unknown option '{}'", arg)),
+            }
+        }
+
+        Ok(options)
+    }
+
+    // Picking a track is what skips the menus
+    pub fn skip_menus(&self) -> bool {
+        self.track.is_some()
+    }
+
+    pub fn apply(&self, data: &mut GameData) {
+        if let Some(laps) = self.laps {
+            data.laps = laps;
+        }
+
+        if self.track.is_some() {
+            data.selected_track = self.track;
+        }
+
+        let player = &mut data.players[0];
+        if self.car.is_some() {
+            player.car = self.car;
+        }
+        if self.control.is_some() {
+            player.control = self.control;
+        }
+    }
+}
+
+// 1-based, matching the numbers shown in the select screen
+fn parse_choice<T: Copy>(value: &str, all: &[T]) -> Result<T, String> {
+    value
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| all.get(i).copied())
+        .ok_or(format!(
+            "expected a number from 1 to {}, got '{}'",
+            all.len(),
+            value
+        ))
+}
+
+fn parse_control(value: &str) -> Result<ControlChoice, String> {
+    match value {
+        "keyboard" => Ok(ControlChoice::Keyboard),
+        "wasd" => Ok(ControlChoice::KeyboardLeft),
+        "arrows" => Ok(ControlChoice::KeyboardRight),
+        _ => value
+            .strip_prefix("pad")
+            .and_then(|n| n.parse::<i32>().ok())
+            .filter(|&n| n >= 1)
+            .map(|n| ControlChoice::Controller(n - 1))
+            .ok_or(format!("unknown control scheme '{}'", value)),
+    }
+}
+
+fn parse_laps(value: &str) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|laps| (1..=MAX_LAPS).contains(laps))
+        .ok_or(format!(
+            "expected a lap count from 1 to {}, got '{}'",
+            MAX_LAPS, value
+        ))
+}
+
+fn parse_seed(value: &str) -> Result<TrackSeed, String> {
+    value
+        .trim_start_matches('#')
+        .parse::<u32>()
+        .map(TrackSeed::new)
+        .map_err(|_| format!("expected a track seed like 1234, got '{}'", value))
+}
+
+fn parse_size(value: &str) -> Result<(i32, i32), String> {
+    let size = value
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse::<i32>().ok()?, h.parse::<i32>().ok()?)));
+
+    match size {
+        Some((w, h)) if w > 0 && h > 0 => Ok((w, h)),
+        _ => Err(format!("expected a size like 1280x720, got '{}'", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<LaunchOptions, String> {
+        LaunchOptions::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn no_options_go_through_the_menus() {
+        let options = parse(&[]).unwrap();
+
+        assert!(!options.skip_menus());
+        assert!(options.car.is_none() && options.laps.is_none());
+        assert!(!options.fullscreen && !options.mute && !options.dev && !options.help);
+    }
+
+    #[test]
+    fn a_track_or_seed_skips_the_menus() {
+        let options = parse(&["--track", "2", "--car", "3", "--laps", "5"]).unwrap();
+        assert!(options.skip_menus());
+        assert!(options.track == Some(TrackChoice::Track2));
+        assert!(options.car == Some(CarChoice::Car3));
+        assert_eq!(options.laps, Some(5));
+
+        let options = parse(&["--seed", "#1234"]).unwrap();
+        assert!(options.skip_menus());
+        assert!(matches!(options.track, Some(TrackChoice::Random(_))));
+
+        // Just a car is still picked in the menus
+        assert!(!parse(&["--car", "1"]).unwrap().skip_menus());
+    }
+
+    #[test]
+    fn flags_and_values_parse() {
+        let options = parse(&[
+            "--control",
+            "pad2",
+            "--window",
+            "1280x720",
+            "--fullscreen",
+            "--mute",
+            "--dev",
+            "-h",
+        ])
+        .unwrap();
+
+        assert!(options.control == Some(ControlChoice::Controller(1)));
+        assert_eq!(options.window_size, Some((1280, 720)));
+        assert!(options.fullscreen && options.mute && options.dev && options.help);
+    }
+
+    #[test]
+    fn unknown_options_are_rejected() {
+        let error = parse(&["--turbo"]).err().unwrap();
+        assert!(error.contains("--turbo"));
+
+        assert!(parse(&["track", "1"]).is_err());
+    }
+
+    #[test]
+    fn options_missing_their_value_are_rejected() {
+        for flag in [
+            "--track",
+            "--seed",
+            "--car",
+            "--control",
+            "--laps",
+            "--window",
+        ] {
+            let error = parse(&[flag]).err().unwrap();
+            assert_eq!(error, format!("{} needs a value", flag));
+        }
+    }
+
+    #[test]
+    fn bad_values_are_rejected() {
+        let bad: [&[&str]; 8] = [
+            &["--track", "0"],
+            &["--track", "5"],
+            &["--car", "fast"],
+            &["--laps", "0"],
+            &["--laps", "11"],
+            &["--control", "pad0"],
+            &["--window", "1280"],
+            &["--seed", "abc"],
+        ];
+        for args in bad {
+            assert!(parse(args).is_err(), "{:?}", args);
+        }
+    }
+}
@@ -1,8 +1,31 @@
+pub mod ai;
+pub mod assets;
+pub mod audio;
+pub mod car;
+pub mod championship;
+pub mod championship_scene;
+pub mod collision;
+pub mod display;
+pub mod drift;
 pub mod game_data;
 pub mod game_scene;
+pub mod hot_reload;
+pub mod hud;
+pub mod input;
+pub mod launch;
+pub mod medals;
 pub mod menu_scene;
+pub mod music;
+pub mod race;
+pub mod records;
+pub mod rush;
 pub mod scenes;
 pub mod select_scene;
 pub mod settings_scene;
+pub mod track;
+pub mod track_check;
+pub mod track_editor_scene;
+pub mod track_generator;
+pub mod track_manifest;
+pub mod ui;
 pub mod utils;
-//pub mod pause_scene;
@@ -1,45 +1,128 @@
-use racingGame::game_data::GameData;
-use racingGame::menu_scene::MenuScene;
-use racingGame::scenes::SceneManager;
+use racing_game::assets::{AssetError, report};
+use racing_game::audio::{SoundCategory, SoundEffects};
+use racing_game::car::{CAR_DATA_PATH, CarTable};
+use racing_game::championship::CHAMPIONSHIP_PATH;
+use racing_game::display::{
+    DISPLAY_SETTINGS_PATH, DisplaySettings, MIN_WINDOW_HEIGHT, MIN_WINDOW_WIDTH, WindowMode,
+};
+use racing_game::game_data::GameData;
+use racing_game::game_scene::GameScene;
+use racing_game::hot_reload::AssetWatcher;
+use racing_game::launch::{LaunchOptions, USAGE};
+use racing_game::menu_scene::MenuScene;
+use racing_game::music::{MusicPlayer, Playlist};
+use racing_game::records::{RECORDS_PATH, Records};
+use racing_game::scenes::{SceneManager, SceneSwitch};
 use raylib::prelude::*;
 
 use std::time::Instant;
 
 fn main() {
-    // Game window dimensions
-    let width = 960;
-    let height = 720;
+    let options = match LaunchOptions::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    if options.help {
+        println!("{}", USAGE);
+        return;
+    }
+
+    // Saved from the settings screen, the command line wins for this run
+    let mut display = DisplaySettings::load();
+    if let Some(size) = options.window_size {
+        display.resolution = size;
+        display.mode = WindowMode::Windowed;
+    }
+    if options.fullscreen {
+        display.mode = WindowMode::Fullscreen;
+    }
 
     // Creating game window
+    let (width, height) = display.resolution;
     let (mut rl, thread) = raylib::init()
         .size(width, height)
         .title("WD40:Rust-Off")
+        .resizable()
         .build();
 
-    let audio = RaylibAudio::init_audio_device().unwrap();
-    audio.set_master_volume(0.2);
+    // Escape is used to back out of menus, so it must not close the window
+    rl.set_exit_key(None);
+    rl.set_window_min_size(MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT);
+    display.apply(&mut rl);
+
+    // Without an audio device or any music the game simply runs silent.
+    // GameData borrows the device, so it's closed once the game is done.
+    let audio = RaylibAudio::init_audio_device()
+        .map_err(|e| report(&AssetError::NoAudioDevice(e.to_string())))
+        .ok();
+    let audio = audio.as_ref();
 
-    let music = audio.new_music("./Assets/song.mp3").unwrap();
+    let mut game_data = GameData::new(rl.get_screen_width(), rl.get_screen_height());
+    game_data.sounds = audio.map(SoundEffects::load);
+    game_data.music = audio.map(MusicPlayer::new);
+    game_data.volumes.muted = options.mute;
+    game_data.display = display;
+
+    if let Some(music) = &mut game_data.music {
+        music.play(Playlist::Menu);
+    }
 
-    music.play_stream();
+    game_data.car_table = CarTable::load(CAR_DATA_PATH).unwrap_or_else(|e| {
+        report(&e);
+        CarTable::default()
+    });
 
-    let mut game_data = GameData::new(width, height);
+    game_data.records = Records::load();
+
+    options.apply(&mut game_data);
+
+    // --dev reloads edited assets into the running game, but not the
+    // files it saves there itself
+    if options.dev {
+        let saves = [RECORDS_PATH, DISPLAY_SETTINGS_PATH, CHAMPIONSHIP_PATH];
+        game_data.watcher = Some(AssetWatcher::new("Assets", &saves));
+    }
 
     let menu_scene = MenuScene::new(&mut rl, &thread);
     let mut scene_manager =
         SceneManager::new(&mut rl, Box::new(menu_scene), &mut game_data, &thread);
 
+    // Race straight away, the menu stays underneath for when it ends
+    if options.skip_menus() {
+        let game_scene = GameScene::new(&mut rl, &thread, Vector2::new(100.0, 100.0), 90.0);
+        scene_manager.apply_switch(
+            SceneSwitch::Push(Box::new(game_scene)),
+            &mut rl,
+            &mut game_data,
+            &thread,
+        );
+    }
+
     let mut last_time = Instant::now();
 
     while !rl.window_should_close() && !scene_manager.should_quit() {
-        music.update_stream();
         let temp = Instant::now();
         let delta = (temp - last_time).as_secs_f32();
         last_time = temp;
 
+        let volumes = game_data.volumes;
+        if let Some(audio) = audio {
+            audio.set_master_volume(volumes.master_level());
+        }
+        if let Some(music) = &mut game_data.music {
+            music.update(delta, volumes.get(SoundCategory::Music));
+        }
+
         scene_manager.update(&mut rl, delta, &mut game_data, &thread);
 
+        scene_manager.draw_offscreen(&mut rl, &thread, &mut game_data);
         let mut d = rl.begin_drawing(&thread);
         scene_manager.draw(&mut d, &mut game_data);
     }
+
+    scene_manager.shut_down(&mut rl, &mut game_data, &thread);
 }
@@ -0,0 +1,270 @@
+use raylib::prelude::*;
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::assets::AssetError;
+use crate::car::CarClass;
+
+// -------- Time trial medals --------
+// Ordered worst to best, so `max` keeps the better of two
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Medal {
+    // Best first
+    pub const ALL: [Medal; 3] = [Self::Gold, Self::Silver, Self::Bronze];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gold => "Gold",
+            Self::Silver => "Silver",
+            Self::Bronze => "Bronze",
+        }
+    }
+
+    // As written in track data and the records file
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Gold => "gold",
+            Self::Silver => "silver",
+            Self::Bronze => "bronze",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|medal| medal.key() == key)
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Self::Gold => Color::GOLD,
+            Self::Silver => Color::new(200, 200, 210, 255),
+            Self::Bronze => Color::new(205, 127, 50, 255),
+        }
+    }
+
+    // The one to chase once `earned` is held, gold when there's none better
+    pub fn next_target(earned: Option<Medal>) -> Medal {
+        match earned {
+            None => Self::Bronze,
+            Some(Self::Bronze) => Self::Silver,
+            Some(_) => Self::Gold,
+        }
+    }
+}
+
+// Lap times in seconds to beat for each medal
+#[derive(Copy, Clone)]
+pub struct MedalTimes {
+    pub gold: f32,
+    pub silver: f32,
+    pub bronze: f32,
+}
+
+impl MedalTimes {
+    pub fn time(&self, medal: Medal) -> f32 {
+        match medal {
+            Medal::Gold => self.gold,
+            Medal::Silver => self.silver,
+            Medal::Bronze => self.bronze,
+        }
+    }
+
+    // Best medal a lap of `time` earns
+    pub fn medal_for(&self, time: f32) -> Option<Medal> {
+        Medal::ALL
+            .into_iter()
+            .find(|&medal| time <= self.time(medal))
+    }
+}
+
+// -------- A track's medal times for each car class --------
+// Kept in the track's data file, one line a class:
+//   target <class> <gold> <silver> <bronze>
+#[derive(Clone, Default)]
+pub struct MedalTargets {
+    times: Vec<(CarClass, MedalTimes)>,
+}
+
+impl MedalTargets {
+    // Only the `target` lines are read, a custom track's manifest has
+    // its layout in the same file
+    pub fn load(path: &str) -> Result<Self, AssetError> {
+        let text = fs::read_to_string(path).map_err(|_| AssetError::NotFound(path.to_string()))?;
+        let mut targets = Self::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let mut fields = line.split_whitespace();
+            if fields.next() != Some("target") {
+                continue;
+            }
+
+            let fields: Vec<&str> = fields.collect();
+            let (class, times) =
+                Self::parse_line(&fields).map_err(|reason| AssetError::Invalid {
+                    path: path.to_string(),
+                    reason: format!("line {}: {}", i + 1, reason),
+                })?;
+            targets.set(class, times);
+        }
+
+        Ok(targets)
+    }
+
+    // The fields after `target`
+    pub fn parse_line(fields: &[&str]) -> Result<(CarClass, MedalTimes), String> {
+        let [class, gold, silver, bronze] = fields else {
+            return Err("expected 'target <class> <gold> <silver> <bronze>'".to_string());
+        };
+
+        let class = CarClass::ALL
+            .into_iter()
+            .find(|c| c.name() == *class)
+            .ok_or(format!("unknown car class '{}'", class))?;
+        let time = |field: &str| {
+            field
+                .parse::<f32>()
+                .ok()
+                .filter(|&time| time > 0.0)
+                .ok_or(format!("expected a time in seconds, got '{}'", field))
+        };
+        let times = MedalTimes {
+            gold: time(gold)?,
+            silver: time(silver)?,
+            bronze: time(bronze)?,
+        };
+
+        if !(times.gold <= times.silver && times.silver <= times.bronze) {
+            return Err("medal times must go gold, silver, bronze from fastest".to_string());
+        }
+
+        Ok((class, times))
+    }
+
+    pub fn set(&mut self, class: CarClass, times: MedalTimes) {
+        self.times.retain(|(c, _)| *c != class);
+        self.times.push((class, times));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    pub fn get(&self, class: CarClass) -> Option<MedalTimes> {
+        self.times
+            .iter()
+            .find(|(c, _)| *c == class)
+            .map(|(_, times)| *times)
+    }
+
+    // In the same form `load` reads
+    pub fn write(&self, text: &mut String) {
+        for (class, times) in &self.times {
+            let _ = writeln!(
+                text,
+                "target {} {:.2} {:.2} {:.2}",
+                class.name(),
+                times.gold,
+                times.silver,
+                times.bronze
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMES: MedalTimes = MedalTimes {
+        gold: 4.0,
+        silver: 5.0,
+        bronze: 6.0,
+    };
+
+    #[test]
+    fn a_lap_on_a_target_earns_it() {
+        assert!(TIMES.medal_for(4.0) == Some(Medal::Gold));
+        assert!(TIMES.medal_for(5.0) == Some(Medal::Silver));
+        assert!(TIMES.medal_for(6.0) == Some(Medal::Bronze));
+    }
+
+    #[test]
+    fn laps_between_targets_earn_the_slower_one() {
+        assert!(TIMES.medal_for(3.2) == Some(Medal::Gold));
+        assert!(TIMES.medal_for(4.01) == Some(Medal::Silver));
+        assert!(TIMES.medal_for(5.99) == Some(Medal::Bronze));
+    }
+
+    #[test]
+    fn slower_than_every_target_earns_nothing() {
+        assert!(TIMES.medal_for(6.01).is_none());
+    }
+
+    #[test]
+    fn target_lines_parse() {
+        let (class, times) = MedalTargets::parse_line(&["B", "4.4", "5.4", "7.1"]).unwrap();
+
+        assert!(class == CarClass::B);
+        assert_eq!((times.gold, times.silver, times.bronze), (4.4, 5.4, 7.1));
+    }
+
+    #[test]
+    fn bad_target_lines_are_rejected() {
+        let bad: [&[&str]; 5] = [
+            &["B", "4.4", "5.4"],
+            &["D", "4.4", "5.4", "7.1"],
+            &["B", "fast", "5.4", "7.1"],
+            &["B", "0", "5.4", "7.1"],
+            &["B", "5.4", "4.4", "7.1"],
+        ];
+        for fields in bad {
+            assert!(MedalTargets::parse_line(fields).is_err(), "{:?}", fields);
+        }
+    }
+
+    #[test]
+    fn load_reads_only_target_lines() {
+        let path = std::env::temp_dir().join(format!("medals_{}.txt", std::process::id()));
+        let text = "spawn 10 20 90\n\
+                    target A 3.8 4.7 6.3 # hand tuned\n\
+                    # target C 1 2 3\n\
+                    target B 4.4 5.4 7.1\n";
+        fs::write(&path, text).unwrap();
+
+        let targets = MedalTargets::load(&path.to_string_lossy());
+        let _ = fs::remove_file(&path);
+        let targets = targets.ok().unwrap();
+
+        assert!(
+            targets
+                .get(CarClass::A)
+                .is_some_and(|times| times.gold == 3.8)
+        );
+        assert!(
+            targets
+                .get(CarClass::B)
+                .is_some_and(|times| times.bronze == 7.1)
+        );
+        assert!(targets.get(CarClass::C).is_none());
+    }
+
+    #[test]
+    fn written_targets_load_back() {
+        let mut targets = MedalTargets::default();
+        targets.set(CarClass::C, TIMES);
+
+        let mut text = String::new();
+        targets.write(&mut text);
+        let fields: Vec<&str> = text.split_whitespace().skip(1).collect();
+        let (class, times) = MedalTargets::parse_line(&fields).unwrap();
+
+        assert!(class == CarClass::C);
+        assert_eq!((times.gold, times.silver, times.bronze), (4.0, 5.0, 6.0));
+    }
+}
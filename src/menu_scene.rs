@@ -1,35 +1,62 @@
 use raylib::prelude::*;
 
+use crate::assets::TextureHandle;
+use crate::display::content_top;
 use crate::game_data::GameData;
+use crate::input::read_menu_input;
 use crate::scenes::{Scene, SceneSwitch};
 use crate::select_scene::SelectScene;
 use crate::settings_scene::SettingsScene;
-use crate::utils::*;
+use crate::track_editor_scene::TrackEditorScene;
+use crate::ui::*;
 
 pub struct MenuScene {
-    title_texture: Option<Texture2D>,
-    background_texture: Option<Texture2D>,
+    title_texture: Option<TextureHandle>,
+    background_texture: Option<TextureHandle>,
+
+    play_button: Button,
+    settings_button: Button,
+    editor_button: Button,
+    quit_button: Button,
+    focus: Focus,
 }
 
 impl MenuScene {
-    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
-        let title_texture = rl
-            .load_texture(thread, "Assets/title_image.png")
-            .expect("Failed to load title image");
-
-        let menu_texture = rl
-            .load_texture(thread, "Assets/MenuBack.png")
-            .expect("Failed to load menu background image");
-
+    pub fn new(_rl: &mut RaylibHandle, _thread: &RaylibThread) -> Self {
         Self {
-            title_texture: Some(title_texture),
-            background_texture: Some(menu_texture),
+            title_texture: None,
+            background_texture: None,
+            play_button: Button::new("Play"),
+            settings_button: Button::new("Settings"),
+            editor_button: Button::new("Track Editor"),
+            quit_button: Button::new("Quit"),
+            focus: Focus::default(),
         }
     }
+
+    fn layout(&mut self, screen_width: i32, screen_height: i32) {
+        let center_x = screen_width as f32 / 2.0;
+        let top = content_top(screen_height);
+        let mut column = Layout::column(center_x, top + 390.0, 320.0, 50.0, 20.0);
+
+        self.play_button.rect = column.next_rect();
+        self.settings_button.rect = column.next_rect();
+        self.editor_button.rect = column.next_rect();
+        self.quit_button.rect = column.next_rect();
+    }
 }
 
 impl Scene for MenuScene {
-    fn on_enter(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {}
+    fn on_enter(&mut self, rl: &mut RaylibHandle, data: &mut GameData, thread: &RaylibThread) {
+        self.title_texture = Some(data.assets.texture(rl, thread, "Assets/title_image.png"));
+        self.background_texture = Some(data.assets.texture(rl, thread, "Assets/MenuBack.png"));
+
+        self.layout(data.screen_width, data.screen_height);
+    }
+
+    fn on_resize(&mut self, data: &GameData) {
+        self.layout(data.screen_width, data.screen_height);
+    }
 
     fn handle_input(
         &mut self,
@@ -37,38 +64,40 @@ impl Scene for MenuScene {
         data: &mut GameData,
         thread: &RaylibThread,
     ) -> SceneSwitch {
-        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
-            let click = rl.get_mouse_position();
-
-            let center_x = data.screen_width as f32 / 2.0;
-            let button_width = 320.0;
-            let button_height = 50.0;
-            let button_spacing = 20.0;
-            let first_button_y = 360.0;
-
-            let play_button_rectangle = Rectangle::new(
-                (center_x - button_width / 2.0) as f32,
-                first_button_y + 30.0,
-                button_width,
-                button_height,
-            );
-            let settings_button_rectangle = Rectangle::new(
-                play_button_rectangle.x,
-                play_button_rectangle.y + button_height + button_spacing,
-                button_width,
-                button_height,
-            );
+        let nav = read_menu_input(rl);
 
-            if check_collision_point_rect(&click, &play_button_rectangle) {
-                println!("Play button clicked");
-                let select_scene =
-                    SelectScene::new(rl, thread, data.screen_width, data.screen_height);
-                return SceneSwitch::Push(Box::new(select_scene));
-            } else if check_collision_point_rect(&click, &settings_button_rectangle) {
-                println!("Settings button clicked");
-                let settings_scene = SettingsScene::new(rl, thread);
-                return SceneSwitch::Push(Box::new(settings_scene));
-            }
+        // Nothing to go back to from here, so jump to Quit instead
+        if nav.back {
+            self.focus = Focus { row: 3, col: 0 };
+        }
+
+        self.focus.navigate(&nav, &[1, 1, 1, 1]);
+        self.play_button.focused = self.focus.is(0, 0);
+        self.settings_button.focused = self.focus.is(1, 0);
+        self.editor_button.focused = self.focus.is(2, 0);
+        self.quit_button.focused = self.focus.is(3, 0);
+
+        let play_clicked = self.play_button.update(rl, &nav);
+        let settings_clicked = self.settings_button.update(rl, &nav);
+        let editor_clicked = self.editor_button.update(rl, &nav);
+        let quit_clicked = self.quit_button.update(rl, &nav);
+        if play_clicked || settings_clicked || editor_clicked || quit_clicked {
+            data.play_click();
+        }
+
+        if play_clicked {
+            println!("Play button clicked");
+            let select_scene = SelectScene::new(rl, thread, data.screen_width, data.screen_height);
+            return SceneSwitch::Push(Box::new(select_scene));
+        } else if settings_clicked {
+            println!("Settings button clicked");
+            let settings_scene = SettingsScene::new(rl, thread);
+            return SceneSwitch::Push(Box::new(settings_scene));
+        } else if editor_clicked {
+            let editor_scene = TrackEditorScene::new(rl, thread, data);
+            return SceneSwitch::Push(Box::new(editor_scene));
+        } else if quit_clicked {
+            return SceneSwitch::Quit;
         }
 
         SceneSwitch::None
@@ -78,49 +107,19 @@ impl Scene for MenuScene {
         d.clear_background(Color::WHITE);
 
         let center_x = data.screen_width as f32 / 2.0;
-        let button_width = 320.0;
-        let button_height = 50.0;
-        let button_spacing = 20.0;
-        let title_y = 45.0;
-        let first_button_y = 360.0;
+        let title_y = content_top(data.screen_height) + 45.0;
 
         // Resizing the background image to fill screen
         if let Some(texture) = &self.background_texture {
-            let tex_w = texture.width as f32;
-            let tex_h = texture.height as f32;
-
-            let win_w = data.screen_width as f32;
-            let win_h = data.screen_height as f32;
-
-            let scale = (win_w / tex_w).max(win_h / tex_h);
-
-            let dest_w = tex_w * scale;
-            let dest_h = tex_h * scale;
-
-            let dest_x = (win_w - dest_w) / 2.0;
-            let dest_y = (win_h - dest_h) / 2.0;
-
-            let source = Rectangle::new(0.0, 0.0, tex_w, tex_h);
-            let dest = Rectangle::new(dest_x, dest_y, dest_w, dest_h);
-
-            d.draw_texture_pro(texture, source, dest, Vector2::zero(), 0.0, Color::WHITE);
+            draw_background(
+                d,
+                texture,
+                data.screen_width as f32,
+                data.screen_height as f32,
+            );
         }
 
-        let play_button = Rectangle {
-            x: center_x - button_width / 2.0,
-            y: first_button_y + 30.0,
-            width: button_width,
-            height: button_height,
-        };
-
-        let settings_button = Rectangle {
-            x: play_button.x,
-            y: play_button.y + button_height + button_spacing,
-            width: button_width,
-            height: button_height,
-        };
-
-        if let Some(texture) = &self.title_texture {
+        if let Some(texture) = self.title_texture.as_deref() {
             let scale = 1.2;
 
             let tex_w = texture.width as f32 * scale;
@@ -129,37 +128,16 @@ impl Scene for MenuScene {
             d.draw_texture_ex(texture, Vector2::new(x, title_y), 0.0, scale, Color::WHITE);
         }
 
-        let play_text = "Play";
-        let play_text_size = 30;
-        let play_text_width = d.measure_text(play_text, play_text_size);
-
-        let settings_text = "Settings";
-        let settings_text_size = 30;
-        let settings_text_width = d.measure_text(settings_text, settings_text_size);
-
-        d.draw_rectangle_rounded(play_button, 0.4, 12, Color::BURLYWOOD);
-        d.draw_text(
-            play_text,
-            (play_button.x + (button_width - play_text_width as f32) / 2.0) as i32,
-            (play_button.y + (button_height - play_text_size as f32) / 2.0) as i32,
-            play_text_size,
-            Color::BLACK,
-        );
-
-        d.draw_rectangle_rounded(settings_button, 0.4, 12, Color::BURLYWOOD);
-        d.draw_text(
-            settings_text,
-            (settings_button.x + (button_width - settings_text_width as f32) / 2.0) as i32,
-            (settings_button.y + (button_height - settings_text_size as f32) / 2.0) as i32,
-            settings_text_size,
-            Color::BLACK,
-        );
+        self.play_button.draw(d);
+        self.settings_button.draw(d);
+        self.editor_button.draw(d);
+        self.quit_button.draw(d);
     }
 
-    fn on_exit(&mut self, rl: &mut RaylibHandle, _data: &mut GameData, thread: &RaylibThread) {
-        if let Some(texture) = self.title_texture.take() {
-            unsafe { rl.unload_texture(thread, texture.make_weak()) };
-        }
+    fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {
+        // Dropping the handles lets the cache unload the textures
+        self.title_texture = None;
+        self.background_texture = None;
     }
 
     fn update(&mut self, _dt: f32, _data: &mut GameData) -> SceneSwitch {
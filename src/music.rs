@@ -0,0 +1,151 @@
+use raylib::prelude::*;
+use std::fs;
+
+use crate::assets::{AssetError, load_music, report};
+
+const MUSIC_DIR: &str = "Assets/music";
+const EXTENSIONS: [&str; 4] = ["mp3", "ogg", "wav", "flac"];
+// Seconds one track takes to fade into the next
+const CROSSFADE: f32 = 2.0;
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Playlist {
+    Menu,
+    Race,
+}
+
+impl Playlist {
+    pub const ALL: [Playlist; 2] = [Self::Menu, Self::Race];
+
+    // Every track in Assets/music/<folder> is in the playlist
+    fn folder(self) -> String {
+        let name = match self {
+            Playlist::Menu => "menu",
+            Playlist::Race => "race",
+        };
+        format!("{}/{}", MUSIC_DIR, name)
+    }
+}
+
+struct Voice<'aud> {
+    music: Music<'aud>,
+    // Faded in from 0 to 1, or back out again
+    gain: f32,
+}
+
+// -------- Menu and race music, crossfading between tracks --------
+pub struct MusicPlayer<'aud> {
+    audio: &'aud RaylibAudio,
+    playlists: [Vec<String>; 2],
+    playlist: Option<Playlist>,
+    next_track: usize,
+
+    current: Option<Voice<'aud>>,
+    fading_out: Vec<Voice<'aud>>,
+}
+
+impl<'aud> MusicPlayer<'aud> {
+    pub fn new(audio: &'aud RaylibAudio) -> Self {
+        Self {
+            audio,
+            playlists: Playlist::ALL.map(find_tracks),
+            playlist: None,
+            next_track: 0,
+            current: None,
+            fading_out: Vec::new(),
+        }
+    }
+
+    // Fade over to `playlist` from its first track, unless it is already on
+    pub fn play(&mut self, playlist: Playlist) {
+        if self.playlist == Some(playlist) {
+            return;
+        }
+
+        self.playlist = Some(playlist);
+        self.next_track = 0;
+        self.start_next();
+    }
+
+    // Moves the fades along, called once a frame with the music volume
+    pub fn update(&mut self, dt: f32, volume: f32) {
+        // Start the next track before this one ends, so they overlap
+        let ending = self.current.as_ref().is_some_and(|voice| {
+            let left = voice.music.get_time_length() - voice.music.get_time_played();
+            left < CROSSFADE || !voice.music.is_stream_playing()
+        });
+        if ending {
+            self.start_next();
+        }
+
+        let step = dt / CROSSFADE;
+
+        if let Some(voice) = &mut self.current {
+            voice.gain = (voice.gain + step).min(1.0);
+            voice.music.set_volume(voice.gain * volume);
+            voice.music.update_stream();
+        }
+
+        for voice in &mut self.fading_out {
+            voice.gain -= step;
+            voice.music.set_volume(voice.gain.max(0.0) * volume);
+            voice.music.update_stream();
+        }
+        self.fading_out.retain(|voice| voice.gain > 0.0);
+    }
+
+    fn start_next(&mut self) {
+        if let Some(voice) = self.current.take() {
+            self.fading_out.push(voice);
+        }
+
+        let Some(playlist) = self.playlist else {
+            return;
+        };
+        let tracks = &mut self.playlists[playlist as usize];
+
+        // Tracks that fail to load are reported and dropped from the list
+        while !tracks.is_empty() {
+            let index = self.next_track % tracks.len();
+            match load_music(self.audio, &tracks[index]) {
+                Ok(mut music) => {
+                    // Looping would never let the stream end and move on
+                    music.looping = false;
+                    music.set_volume(0.0);
+                    music.play_stream();
+
+                    self.next_track = index + 1;
+                    self.current = Some(Voice { music, gain: 0.0 });
+                    return;
+                }
+                Err(error) => {
+                    report(&error);
+                    tracks.remove(index);
+                }
+            }
+        }
+    }
+}
+
+// Music files in the playlist's folder, in name order
+fn find_tracks(playlist: Playlist) -> Vec<String> {
+    let folder = playlist.folder();
+    let Ok(entries) = fs::read_dir(&folder) else {
+        report(&AssetError::NotFound(folder));
+        return Vec::new();
+    };
+
+    let mut tracks: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+
+    tracks.sort();
+    tracks
+}
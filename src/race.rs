@@ -0,0 +1,111 @@
+use raylib::prelude::*;
+use std::cmp::Ordering;
+
+use crate::car::{CAR_WIDTH, Car};
+use crate::collision::resolve_car_collisions;
+use crate::track::{Checkpoint, Surface};
+
+const GRID_ROW_GAP: f32 = 60.0;
+const GRID_LANE_OFFSET: f32 = 14.0;
+
+// -------- Stagger cars two abreast behind the spawn --------
+pub fn grid_position(spawn: Vector2, direction_deg: f32, index: usize, count: usize) -> Vector2 {
+    if count == 1 {
+        return spawn;
+    }
+
+    let rad = direction_deg.to_radians();
+    let forward = Vector2::new(rad.cos(), rad.sin());
+    let side = Vector2::new(-forward.y, forward.x);
+
+    let row = (index / 2) as f32;
+    let lane = if index.is_multiple_of(2) { -1.0 } else { 1.0 };
+
+    spawn - forward * row * GRID_ROW_GAP + side * lane * GRID_LANE_OFFSET
+}
+
+// -------- Offset spawn so entire car is on white --------
+// Nudged back by half the car's width, which clears the track outline
+pub fn offset_spawn_for_car(spawn: Vector2, direction_deg: f32) -> Vector2 {
+    let half_len = CAR_WIDTH / 2.0;
+    let rad = direction_deg.to_radians();
+    let backward = Vector2::new(rad.cos(), rad.sin());
+
+    spawn - backward * half_len
+}
+
+// -------- One physics step for every car in the race --------
+// Shared by GameScene and the headless simulator so both agree.
+// `surface_at` looks up the ground at a screen position.
+// Returns the number of new hits between cars.
+pub fn step_race(
+    cars: &mut [Car],
+    checkpoints: &[Checkpoint],
+    mut surface_at: impl FnMut(Vector2) -> Surface,
+    dt: f32,
+    laps: u32,
+    ghost_cars: bool,
+) -> u32 {
+    let previous: Vec<Vector2> = cars.iter().map(|car| car.position).collect();
+
+    for car in cars.iter_mut().filter(|car| !car.retired) {
+        car.surface = surface_at(car.position);
+        car.update(dt);
+    }
+
+    let hits = if ghost_cars {
+        0
+    } else {
+        resolve_car_collisions(cars)
+    };
+
+    // Checked after collisions so a shove across a gate still counts
+    for (car, previous) in cars.iter_mut().zip(previous) {
+        if car.retired {
+            continue;
+        }
+        car.progress
+            .advance(checkpoints, previous, car.position, car.race_time, laps);
+    }
+
+    hits
+}
+
+// -------- Place of each car in the race, 1 is leading --------
+// Finished cars by their time, the rest by gates passed and then how close
+// they are to the next one. Retired cars come last, the latest out first.
+pub fn race_places(cars: &[Car], checkpoints: &[Checkpoint]) -> Vec<usize> {
+    let gates_passed = |car: &Car| car.progress.gates_passed(checkpoints.len());
+    let to_next_gate = |car: &Car| {
+        checkpoints
+            .get(car.progress.next_checkpoint % checkpoints.len().max(1))
+            .map_or(0.0, |gate| {
+                car.position.distance_to((gate.start + gate.end) / 2.0)
+            })
+    };
+
+    let mut order: Vec<usize> = (0..cars.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&cars[a], &cars[b]);
+        if a.retired || b.retired {
+            return a
+                .retired
+                .cmp(&b.retired)
+                .then(b.race_time.total_cmp(&a.race_time));
+        }
+        match (a.progress.finished, b.progress.finished) {
+            (true, true) => a.race_time.total_cmp(&b.race_time),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => gates_passed(b)
+                .cmp(&gates_passed(a))
+                .then(to_next_gate(a).total_cmp(&to_next_gate(b))),
+        }
+    });
+
+    let mut places = vec![0; cars.len()];
+    for (place, &index) in order.iter().enumerate() {
+        places[index] = place + 1;
+    }
+    places
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+
+use crate::assets::{AssetError, report};
+use crate::car::CarClass;
+use crate::game_data::GameMode;
+use crate::medals::Medal;
+
+pub const RECORDS_PATH: &str = "Assets/records.txt";
+// Drift scores kept for each track's leaderboard
+pub const DRIFT_BOARD_SIZE: usize = 5;
+
+// -------- The player's best times, scores and medals, kept between runs --------
+// Tracks are keyed by `track_name`, so a random track's record belongs to
+// its seed. Race times are per track, lap count and mode, and medals per
+// track and car class.
+#[derive(Default)]
+pub struct Records {
+    laps: HashMap<String, f32>,
+    races: HashMap<String, f32>,
+    // Each track's top drift scores, best first
+    drifts: HashMap<String, Vec<f32>>,
+    // Checkpoints reached in a checkpoint rush, also higher is better
+    rushes: HashMap<String, f32>,
+    medals: HashMap<String, Medal>,
+}
+
+impl Records {
+    // Empty on a first run, or if the file can't be read
+    pub fn load() -> Self {
+        let Ok(text) = fs::read_to_string(RECORDS_PATH) else {
+            return Self::default();
+        };
+
+        Self::parse(&text).unwrap_or_else(|reason| {
+            report(&AssetError::Invalid {
+                path: RECORDS_PATH.to_string(),
+                reason,
+            });
+            Self::default()
+        })
+    }
+
+    // One record a line: `lap`, `race`, `drift` or `rush`, the time or score, then
+    // the track name, or `medal`, which one, then the track and class. Each
+    // score on a drift leaderboard has its own line.
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut records = Self::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let invalid = || {
+                format!(
+                    "line {}: expected 'lap|race|drift|rush <value> <track>' or 'medal <medal> <track>'",
+                    i + 1
+                )
+            };
+            let mut fields = line.splitn(3, ' ');
+            let (Some(kind), Some(time), Some(track)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid());
+            };
+
+            if kind == "medal" {
+                let medal = Medal::from_key(time).ok_or_else(invalid)?;
+                records.medals.insert(track.trim().to_string(), medal);
+                continue;
+            }
+
+            let time: f32 = time.parse().map_err(|_| invalid())?;
+
+            if kind == "drift" {
+                records.record_drift(track.trim(), time);
+                continue;
+            }
+
+            let table = match kind {
+                "lap" => &mut records.laps,
+                "race" => &mut records.races,
+                "rush" => &mut records.rushes,
+                _ => return Err(invalid()),
+            };
+            table.insert(track.trim().to_string(), time);
+        }
+
+        Ok(records)
+    }
+
+    pub fn save(&self) -> Result<(), AssetError> {
+        fs::write(RECORDS_PATH, self.to_text()).map_err(|e| AssetError::Invalid {
+            path: RECORDS_PATH.to_string(),
+            reason: e.to_string(),
+        })
+    }
+
+    // In the form `parse` reads
+    fn to_text(&self) -> String {
+        let mut text = String::from("# Best times and medals, written after each race\n");
+
+        // Sorted so the file doesn't reshuffle every save
+        let tables = [
+            ("lap", &self.laps),
+            ("race", &self.races),
+            ("rush", &self.rushes),
+        ];
+        for (kind, table) in tables {
+            let mut entries: Vec<_> = table.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (track, time) in entries {
+                let _ = writeln!(text, "{} {:.3} {}", kind, time, track);
+            }
+        }
+
+        let mut boards: Vec<_> = self.drifts.iter().collect();
+        boards.sort_by(|a, b| a.0.cmp(b.0));
+        for (track, scores) in boards {
+            for score in scores {
+                let _ = writeln!(text, "drift {:.3} {}", score, track);
+            }
+        }
+
+        let mut medals: Vec<_> = self.medals.iter().collect();
+        medals.sort_by(|a, b| a.0.cmp(b.0));
+        for (key, medal) in medals {
+            let _ = writeln!(text, "medal {} {}", medal.key(), key);
+        }
+
+        text
+    }
+
+    pub fn best_lap(&self, track: &str) -> Option<f32> {
+        self.laps.get(track).copied()
+    }
+
+    pub fn best_race(&self, track: &str, laps: u32, mode: GameMode) -> Option<f32> {
+        self.races.get(&race_key(track, laps, mode)).copied()
+    }
+
+    // Returns true when either time beat the record. The race time is
+    // `laps` long, in `mode`.
+    pub fn record(
+        &mut self,
+        track: &str,
+        laps: u32,
+        mode: GameMode,
+        lap: Option<f32>,
+        race: Option<f32>,
+    ) -> bool {
+        let entries = [
+            (&mut self.laps, track.to_string(), lap),
+            (&mut self.races, race_key(track, laps, mode), race),
+        ];
+
+        let mut improved = false;
+        for (table, key, time) in entries {
+            let Some(time) = time else {
+                continue;
+            };
+            if table.get(&key).is_none_or(|&best| time < best) {
+                table.insert(key, time);
+                improved = true;
+            }
+        }
+
+        improved
+    }
+
+    pub fn best_drift(&self, track: &str) -> Option<f32> {
+        self.drift_board(track).first().copied()
+    }
+
+    // The track's top drift scores, best first
+    pub fn drift_board(&self, track: &str) -> &[f32] {
+        self.drifts.get(track).map_or(&[], Vec::as_slice)
+    }
+
+    // The place it took on the leaderboard, 1 for the top, or None when
+    // it didn't make it. A tie goes below the score already there.
+    pub fn record_drift(&mut self, track: &str, score: f32) -> Option<usize> {
+        if score <= 0.0 {
+            return None;
+        }
+
+        let board = self.drifts.entry(track.to_string()).or_default();
+        let place = board.iter().take_while(|&&best| best >= score).count();
+        if place >= DRIFT_BOARD_SIZE {
+            return None;
+        }
+
+        board.insert(place, score);
+        board.truncate(DRIFT_BOARD_SIZE);
+        Some(place + 1)
+    }
+
+    pub fn best_rush(&self, track: &str) -> Option<u32> {
+        self.rushes.get(track).map(|&gates| gates as u32)
+    }
+
+    // Returns true when it reached more checkpoints than the best run
+    pub fn record_rush(&mut self, track: &str, gates: u32) -> bool {
+        record_high(&mut self.rushes, track, gates as f32)
+    }
+
+    pub fn medal(&self, track: &str, class: CarClass) -> Option<Medal> {
+        self.medals.get(&medal_key(track, class)).copied()
+    }
+
+    // Returns true when it's better than the one held
+    pub fn award(&mut self, track: &str, class: CarClass, medal: Medal) -> bool {
+        let key = medal_key(track, class);
+        if self.medals.get(&key).is_some_and(|&held| held >= medal) {
+            return false;
+        }
+        self.medals.insert(key, medal);
+        true
+    }
+}
+
+// A race only compares with others as long, in the same mode
+fn race_key(track: &str, laps: u32, mode: GameMode) -> String {
+    format!("{} / {} laps / {}", track, laps, mode.name())
+}
+
+fn medal_key(track: &str, class: CarClass) -> String {
+    format!("{} / Class {}", track, class.name())
+}
+
+// Keep the score if it's the highest for the track
+fn record_high(table: &mut HashMap<String, f32>, track: &str, score: f32) -> bool {
+    if table.get(track).is_some_and(|&best| best >= score) {
+        return false;
+    }
+    table.insert(track.to_string(), score);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_shorter_race_keeps_its_own_best() {
+        let mut records = Records::default();
+        assert!(records.record("Track 1", 3, GameMode::Race, Some(5.0), Some(16.0)));
+        assert!(records.record("Track 1", 1, GameMode::Race, Some(5.5), Some(5.5)));
+
+        assert_eq!(records.best_race("Track 1", 3, GameMode::Race), Some(16.0));
+        assert_eq!(records.best_race("Track 1", 1, GameMode::Race), Some(5.5));
+        assert_eq!(records.best_lap("Track 1"), Some(5.0));
+    }
+
+    #[test]
+    fn modes_keep_their_own_best() {
+        let mut records = Records::default();
+        records.record("Track 1", 3, GameMode::TimeTrial, None, Some(12.0));
+        records.record("Track 1", 3, GameMode::Race, None, Some(16.0));
+
+        assert_eq!(
+            records.best_race("Track 1", 3, GameMode::TimeTrial),
+            Some(12.0)
+        );
+        assert_eq!(records.best_race("Track 1", 3, GameMode::Race), Some(16.0));
+        assert_eq!(records.best_race("Track 1", 5, GameMode::Race), None);
+    }
+
+    #[test]
+    fn race_times_round_trip() {
+        let mut records = Records::default();
+        records.record("Track 2", 3, GameMode::Championship, Some(4.5), Some(14.5));
+
+        let loaded = Records::parse(&records.to_text()).unwrap();
+
+        assert_eq!(
+            loaded.best_race("Track 2", 3, GameMode::Championship),
+            Some(14.5)
+        );
+        assert_eq!(loaded.best_lap("Track 2"), Some(4.5));
+    }
+
+    #[test]
+    fn the_drift_board_keeps_the_top_scores() {
+        let mut records = Records::default();
+        for score in [300.0, 100.0, 500.0, 200.0, 400.0] {
+            assert!(records.record_drift("Track 1", score).is_some());
+        }
+        assert_eq!(
+            records.drift_board("Track 1"),
+            [500.0, 400.0, 300.0, 200.0, 100.0]
+        );
+
+        assert_eq!(records.record_drift("Track 1", 350.0), Some(3));
+        assert_eq!(
+            records.drift_board("Track 1"),
+            [500.0, 400.0, 350.0, 300.0, 200.0]
+        );
+
+        // Too low for a full board, or level with the last place
+        assert_eq!(records.record_drift("Track 1", 150.0), None);
+        assert_eq!(records.record_drift("Track 1", 200.0), None);
+        assert_eq!(records.best_drift("Track 1"), Some(500.0));
+        assert!(records.drift_board("Track 2").is_empty());
+    }
+
+    #[test]
+    fn drift_boards_round_trip() {
+        let mut records = Records::default();
+        for score in [120.0, 480.5, 260.0] {
+            records.record_drift("Track 3", score);
+        }
+
+        let loaded = Records::parse(&records.to_text()).unwrap();
+        assert_eq!(loaded.drift_board("Track 3"), [480.5, 260.0, 120.0]);
+    }
+}
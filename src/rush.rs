@@ -0,0 +1,165 @@
+use crate::car::{Car, METERS_PER_PIXEL};
+use crate::track::Checkpoint;
+
+// Seconds on the clock before the first gate
+pub const START_TIME: f32 = 10.0;
+// Each gate is worth the time to reach the next one at this pace, in
+// metres a second, which rises every lap so the clock wins in the end
+const BONUS_PACE: f32 = 14.0;
+const PACE_RISE: f32 = 0.08;
+const MIN_BONUS: f32 = 0.5;
+
+// Time added at a gate
+#[derive(Copy, Clone)]
+pub struct Bonus {
+    pub seconds: f32,
+    // The car's race time when it crossed
+    pub at: f32,
+}
+
+// -------- One car's clock in a checkpoint rush --------
+// The car's race time still counts up, and the clock is what's banked
+// less that, so it stops with the car the same way.
+pub struct RushClock {
+    // The start time plus every bonus so far
+    bank: f32,
+    // Checkpoints reached, the run's score
+    pub gates: usize,
+    pub last_bonus: Option<Bonus>,
+}
+
+impl RushClock {
+    pub fn new() -> Self {
+        Self {
+            bank: START_TIME,
+            gates: 0,
+            last_bonus: None,
+        }
+    }
+
+    pub fn time_left(&self, car: &Car) -> f32 {
+        (self.bank - car.race_time).max(0.0)
+    }
+
+    pub fn out_of_time(&self, car: &Car) -> bool {
+        car.race_time >= self.bank
+    }
+
+    // Bonus time for every gate crossed since the last update
+    pub fn update(&mut self, car: &Car, checkpoints: &[Checkpoint]) {
+        if checkpoints.len() < 2 {
+            return;
+        }
+
+        let gates = car.progress.gates_passed(checkpoints.len());
+        while self.gates < gates {
+            self.gates += 1;
+            let seconds = gate_bonus(checkpoints, self.gates);
+            self.bank += seconds;
+            self.last_bonus = Some(Bonus {
+                seconds,
+                at: car.race_time,
+            });
+        }
+    }
+}
+
+impl Default for RushClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Time to drive from the gate just reached to the next one, at the pace
+// for the lap it's on
+fn gate_bonus(checkpoints: &[Checkpoint], passed: usize) -> f32 {
+    let count = checkpoints.len();
+    let from = checkpoints[passed % count].center();
+    let to = checkpoints[(passed + 1) % count].center();
+
+    let lap = (passed / count) as i32;
+    let pace = BONUS_PACE * (1.0 + PACE_RISE).powi(lap);
+
+    (from.distance_to(to) * METERS_PER_PIXEL / pace).max(MIN_BONUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_data::CarChoice;
+    use raylib::prelude::Vector2;
+
+    // Four gates round a square, each side 30 metres
+    fn gates() -> Vec<Checkpoint> {
+        let side = 30.0 / METERS_PER_PIXEL;
+        [(0.0, 0.0), (side, 0.0), (side, side), (0.0, side)]
+            .map(|(x, y)| Checkpoint {
+                start: Vector2::new(x, y - 10.0),
+                end: Vector2::new(x, y + 10.0),
+            })
+            .to_vec()
+    }
+
+    fn car() -> Car {
+        Car::new(CarChoice::Car1, None, Vector2::zero(), 0.0)
+    }
+
+    #[test]
+    fn the_clock_counts_down_with_race_time() {
+        let (clock, mut car) = (RushClock::new(), car());
+        assert_eq!(clock.time_left(&car), START_TIME);
+
+        car.race_time = 4.0;
+        assert_eq!(clock.time_left(&car), START_TIME - 4.0);
+        assert!(!clock.out_of_time(&car));
+    }
+
+    #[test]
+    fn each_gate_adds_the_time_to_the_next() {
+        let (mut clock, mut car, gates) = (RushClock::new(), car(), gates());
+        let side_time = 30.0 / BONUS_PACE;
+
+        car.race_time = 2.0;
+        car.progress.next_checkpoint = 2;
+        clock.update(&car, &gates);
+
+        assert_eq!(clock.gates, 1);
+        let bonus = clock.last_bonus.unwrap();
+        assert!((bonus.seconds - side_time).abs() < 0.01);
+        assert_eq!(bonus.at, 2.0);
+        assert!((clock.time_left(&car) - (START_TIME + side_time - 2.0)).abs() < 0.01);
+
+        // Two gates in one step both count
+        car.progress.next_checkpoint = 4;
+        clock.update(&car, &gates);
+        assert_eq!(clock.gates, 3);
+    }
+
+    #[test]
+    fn later_laps_are_worth_less() {
+        let gates = gates();
+        let first = gate_bonus(&gates, 1);
+        let next_lap = gate_bonus(&gates, 1 + gates.len());
+
+        assert!((next_lap - first / (1.0 + PACE_RISE)).abs() < 0.01);
+    }
+
+    #[test]
+    fn the_run_ends_when_the_clock_runs_out() {
+        let (mut clock, mut car, gates) = (RushClock::new(), car(), gates());
+        car.progress.next_checkpoint = 2;
+        clock.update(&car, &gates);
+        let bank = START_TIME + clock.last_bonus.unwrap().seconds;
+
+        car.race_time = bank - 0.1;
+        assert!(!clock.out_of_time(&car));
+
+        car.race_time = bank;
+        assert!(clock.out_of_time(&car));
+        assert_eq!(clock.time_left(&car), 0.0);
+
+        car.race_time = bank + 1.0;
+        assert_eq!(clock.time_left(&car), 0.0);
+        assert_eq!(clock.gates, 1);
+    }
+}
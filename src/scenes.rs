@@ -1,5 +1,8 @@
 use raylib::prelude::*;
 
+use crate::assets::{draw_error_banner, report};
+use crate::car::{CAR_DATA_PATH, CarTable};
+use crate::display::fullscreen_pressed;
 use crate::game_data::GameData;
 
 pub enum SceneSwitch {
@@ -7,6 +10,8 @@ pub enum SceneSwitch {
     Push(Box<dyn Scene>),
     Replace(Box<dyn Scene>),
     Pop,
+    // Pop everything above the bottom scene, i.e. back to the main menu
+    PopToRoot,
     Quit,
 }
 
@@ -29,6 +34,28 @@ pub trait Scene {
     fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData);
 
     fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {}
+
+    // The window changed size, `data` already holds the new one
+    fn on_resize(&mut self, _data: &GameData) {}
+
+    // Called before the frame is started, for drawing into render textures
+    fn draw_offscreen(
+        &mut self,
+        _rl: &mut RaylibHandle,
+        _thread: &RaylibThread,
+        _data: &mut GameData,
+    ) {
+    }
+
+    // Dev mode only, `changed` holds the asset paths edited on disk
+    fn on_assets_changed(
+        &mut self,
+        _rl: &mut RaylibHandle,
+        _data: &mut GameData,
+        _thread: &RaylibThread,
+        _changed: &[String],
+    ) {
+    }
 }
 
 pub struct SceneManager {
@@ -62,6 +89,9 @@ impl SceneManager {
         data: &mut GameData,
         thread: &RaylibThread,
     ) {
+        self.reload_changed_assets(rl, dt, data, thread);
+        self.follow_window(rl, data);
+
         if let Some(scene) = self.scenes.last_mut() {
             let switch = scene.handle_input(rl, data, thread);
             self.apply_switch(switch, rl, data, thread);
@@ -73,10 +103,77 @@ impl SceneManager {
         }
     }
 
+    // Every scene in the stack is laid out again, not just the top one,
+    // so going back to one doesn't show the old size
+    fn follow_window(&mut self, rl: &mut RaylibHandle, data: &mut GameData) {
+        if fullscreen_pressed(rl) {
+            data.display.toggle_fullscreen();
+            data.display.apply_and_save(rl);
+        }
+
+        let size = (rl.get_screen_width(), rl.get_screen_height());
+        if size == (data.screen_width, data.screen_height) {
+            return;
+        }
+
+        (data.screen_width, data.screen_height) = size;
+        for scene in &mut self.scenes {
+            scene.on_resize(data);
+        }
+    }
+
+    fn reload_changed_assets(
+        &mut self,
+        rl: &mut RaylibHandle,
+        dt: f32,
+        data: &mut GameData,
+        thread: &RaylibThread,
+    ) {
+        let Some(watcher) = &mut data.watcher else {
+            return;
+        };
+
+        let changed = watcher.poll(dt);
+        if changed.is_empty() {
+            return;
+        }
+
+        for path in &changed {
+            println!("Changed: {}", path);
+            data.assets.invalidate(path);
+        }
+
+        // A broken edit keeps the last good stats
+        if changed.iter().any(|path| path == CAR_DATA_PATH) {
+            match CarTable::load(CAR_DATA_PATH) {
+                Ok(table) => data.car_table = table,
+                Err(error) => report(&error),
+            }
+        }
+
+        for scene in &mut self.scenes {
+            scene.on_assets_changed(rl, data, thread, &changed);
+        }
+    }
+
+    pub fn draw_offscreen(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        data: &mut GameData,
+    ) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.draw_offscreen(rl, thread, data);
+        }
+    }
+
     pub fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
         if let Some(scene) = self.scenes.last() {
             scene.draw(d, data);
         }
+
+        // Asset problems are shown over whatever scene is up
+        draw_error_banner(d, data.screen_width, data.screen_height);
     }
 
     pub fn apply_switch(
@@ -104,13 +201,30 @@ impl SceneManager {
                     old_scene.on_exit(rl, data, thread);
                 }
             }
-            SceneSwitch::Quit => {
-                self.quit = true;
+            SceneSwitch::PopToRoot => {
+                while self.scenes.len() > 1 {
+                    if let Some(mut old_scene) = self.scenes.pop() {
+                        old_scene.on_exit(rl, data, thread);
+                    }
+                }
             }
+            SceneSwitch::Quit => self.shut_down(rl, data, thread),
         }
+
+        // Popped scenes may have held the last handle to some textures
+        data.assets.prune();
     }
 
     pub fn should_quit(&self) -> bool {
         self.quit || self.scenes.is_empty()
     }
+
+    // Every scene left gets its on_exit, so closing the window saves what
+    // leaving them would
+    pub fn shut_down(&mut self, rl: &mut RaylibHandle, data: &mut GameData, thread: &RaylibThread) {
+        while let Some(mut old_scene) = self.scenes.pop() {
+            old_scene.on_exit(rl, data, thread);
+        }
+        self.quit = true;
+    }
 }
@@ -1,80 +1,187 @@
 use raylib::prelude::*;
 
-use crate::game_data::{CarChoice, GameData, TrackChoice};
+use crate::assets::TextureHandle;
+use crate::car::{CarClass, CarStats, METERS_PER_PIXEL, car_name, car_sprite_paths};
+use crate::championship::Championship;
+use crate::championship_scene::ChampionshipScene;
+use crate::display::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH, content_top};
+use crate::game_data::{
+    CarChoice, Difficulty, GameData, GameMode, Knockout, MAX_CARS, MAX_LAPS, MAX_PLAYERS,
+    TrackChoice, control_name, next_control,
+};
 use crate::game_scene::GameScene;
+use crate::input::read_menu_input;
 use crate::scenes::{Scene, SceneSwitch};
-use crate::utils::*;
+use crate::track::{lap_length, track_path};
+use crate::track_generator::TrackSeed;
+use crate::track_manifest::{available_tracks, custom_manifest_path, load_track, track_name};
+use crate::ui::*;
+use crate::utils::format_time;
 
 pub struct SelectScene {
-    background_texture: Option<Texture2D>,
-    car_rects: [Rectangle; 4],
-    track_rects: [Rectangle; 4],
-    play_rect: Rectangle,
+    background_texture: Option<TextureHandle>,
+
+    count_list: List,
+    // Picks from GameMode::ALL and Difficulty::ALL by index
+    mode_slider: Slider,
+    // Laps, or when cars are knocked out in an elimination race
+    laps_slider: Slider,
+    opponents_slider: Slider,
+    difficulty_slider: Slider,
+    track_title: Label,
+    track_list: List,
+    car_title: Label,
+    car_list: List,
+    player_list: List,
+    play_button: Button,
+    back_button: Button,
+
+    // Bundled tracks, any saved from the editor, then a random one
+    tracks: Vec<TrackChoice>,
+    // Filled in on enter, one per track and car
+    track_previews: Vec<TrackPreview>,
+    car_sprites: Vec<TextureHandle>,
+    editing_player: usize,
+    focus: Focus,
 }
 
 impl SelectScene {
     pub fn new(
-        rl: &mut RaylibHandle,
-        thread: &RaylibThread,
+        _rl: &mut RaylibHandle,
+        _thread: &RaylibThread,
         screen_width: i32,
-        _screen_height: i32,
+        screen_height: i32,
     ) -> Self {
-        let background_texture = rl
-            .load_texture(thread, "Assets/selectBack2.png")
-            .expect("Failed to load select background image");
+        let count_labels = (1..=MAX_PLAYERS).map(|count| {
+            if count == 1 {
+                "1 Player".to_string()
+            } else {
+                format!("{} Players", count)
+            }
+        });
+        let mut tracks = available_tracks();
+        tracks.push(TrackChoice::Random(TrackSeed::random()));
+        let track_labels = tracks.iter().map(track_label);
+        let car_labels = (1..=CarChoice::ALL.len()).map(|i| format!("Car {}", i));
+
+        let mut scene = Self {
+            background_texture: None,
+            count_list: List::new(count_labels, 20),
+            mode_slider: choice_slider("Mode", GameMode::ALL.len())
+                .with_format(format_mode)
+                .with_font_size(20),
+            laps_slider: Slider::new("Laps", 3.0, 1.0, MAX_LAPS as f32)
+                .with_step(1.0)
+                .with_format(format_count)
+                .with_font_size(20),
+            opponents_slider: Slider::new("Opponents", 0.0, 0.0, (MAX_CARS - 1) as f32)
+                .with_step(1.0)
+                .with_format(format_count)
+                .with_font_size(20),
+            difficulty_slider: choice_slider("AI Difficulty", Difficulty::ALL.len())
+                .with_format(format_difficulty)
+                .with_font_size(20),
+            track_title: Label::new("Track Select", 40),
+            track_list: List::new(track_labels, 20),
+            car_title: Label::new("Car Select", 40),
+            car_list: List::new(car_labels, 20),
+            // Labels are filled in from the player setup every frame
+            player_list: List::new(vec![String::new(); MAX_PLAYERS], 18),
+            play_button: Button::new("Play"),
+            back_button: Button::new("Back").with_font_size(25),
+            tracks,
+            track_previews: Vec::new(),
+            car_sprites: Vec::new(),
+            editing_player: 0,
+            focus: Focus::default(),
+        };
+        scene.layout(screen_width, screen_height);
+        scene
+    }
+
+    fn layout(&mut self, screen_width: i32, screen_height: i32) {
+        let screen_w = screen_width as f32;
+        let center_x = screen_w / 2.0;
+        let top = content_top(screen_height);
 
         // Layout variables
-        let button_size = 100.0;
+        let button_size: f32 = 100.0;
         let button_spacing = 20.0;
-        let buttons_per_row = 4.0;
-        let row_width = buttons_per_row * button_size + (buttons_per_row - 1.0) * button_spacing;
-        let row_start_x = (screen_width as f32 - row_width) / 2.0;
+        let row = |y: f32, count: usize, width: f32, height: f32| {
+            Layout::row(center_x, y, count, width, height, button_spacing)
+        };
+
+        // Narrower track buttons once the saved ones would run off screen
+        let track_count = self.tracks.len();
+        let track_width = button_size.min((screen_w - 40.0) / track_count as f32 - button_spacing);
+
+        // Player count positions
+        self.count_list
+            .layout(&mut row(top + 70.0, MAX_PLAYERS, button_size, 40.0));
+
+        // Race options, two to a row
+        let mut options = row(top + 120.0, 2, 300.0, 44.0);
+        self.mode_slider.rect = options.next_rect();
+        self.laps_slider.rect = options.next_rect();
+        let mut options = row(top + 170.0, 2, 300.0, 44.0);
+        self.opponents_slider.rect = options.next_rect();
+        self.difficulty_slider.rect = options.next_rect();
 
         // Track positions
-        let track_y = 225.0;
-        let mut track_rects = [Rectangle::new(0.0, 0.0, 0.0, 0.0); 4];
-        for i in 0..4 {
-            track_rects[i] = Rectangle::new(
-                row_start_x + i as f32 * (button_size + button_spacing),
-                track_y,
-                button_size,
-                button_size,
-            );
-        }
+        self.track_title.rect = Rectangle::new(0.0, top + 222.0, screen_w, 36.0);
+        self.track_list.layout(&mut row(
+            top + 262.0,
+            track_count,
+            track_width,
+            button_size + 10.0,
+        ));
 
         // Car positions
-        let car_y = 400.0;
-        let mut car_rects = [Rectangle::new(0.0, 0.0, 0.0, 0.0); 4];
-        for i in 0..4 {
-            car_rects[i] = Rectangle::new(
-                row_start_x + i as f32 * (button_size + button_spacing),
-                car_y,
-                button_size,
-                button_size,
-            );
-        }
+        self.car_title.rect = Rectangle::new(0.0, top + 380.0, screen_w, 36.0);
+        self.car_list.layout(&mut row(
+            top + 420.0,
+            CarChoice::ALL.len(),
+            button_size,
+            button_size,
+        ));
+
+        // Player tabs, pick whose car and controls are being edited
+        self.player_list
+            .layout(&mut row(top + 532.0, MAX_PLAYERS, button_size, 45.0));
 
         // Play button
-        let play_width = 375.0;
-        let play_height = 50.0;
-        let play_rect = Rectangle::new(
-            screen_width as f32 / 2.0 - play_width / 2.0,
-            540.0,
-            play_width,
-            play_height,
-        );
+        self.play_button.rect = Layout::column(center_x, top + 600.0, 375.0, 50.0, 0.0).next_rect();
 
-        Self {
-            background_texture: Some(background_texture),
-            car_rects,
-            track_rects,
-            play_rect,
-        }
+        self.back_button.rect = Rectangle::new(20.0, 20.0, 120.0, 40.0);
     }
 }
 
 impl Scene for SelectScene {
-    fn on_enter(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {}
+    fn on_enter(&mut self, rl: &mut RaylibHandle, data: &mut GameData, thread: &RaylibThread) {
+        self.background_texture = Some(data.assets.texture(rl, thread, "Assets/selectBack2.png"));
+
+        self.car_sprites = CarChoice::ALL
+            .iter()
+            .map(|car| {
+                let [straight, ..] = car_sprite_paths(car);
+                data.assets.texture(rl, thread, &straight)
+            })
+            .collect();
+        self.track_previews = self
+            .tracks
+            .iter()
+            .map(|track| TrackPreview::new(rl, thread, data, *track))
+            .collect();
+
+        // A cup left unfinished last time can be picked up again
+        if data.championship.is_none() {
+            data.championship = Championship::load();
+        }
+    }
+
+    fn on_resize(&mut self, data: &GameData) {
+        self.layout(data.screen_width, data.screen_height);
+    }
 
     fn handle_input(
         &mut self,
@@ -82,44 +189,149 @@ impl Scene for SelectScene {
         data: &mut GameData,
         thread: &RaylibThread,
     ) -> SceneSwitch {
-        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
-            let click = rl.get_mouse_position();
-
-            // Play button
-            if check_collision_point_rect(&click, &self.play_rect) {
-                println!("Play button clicked");
-                return SceneSwitch::Push(Box::new(GameScene::new(
-                    rl,
-                    thread,
-                    Vector2::new(100.0, 100.0),
-                    90.0,
-                )));
+        let nav = read_menu_input(rl);
+        if nav.back {
+            return SceneSwitch::Pop;
+        }
+
+        // A new random track, rolled here or picked up from elsewhere
+        for (preview, track) in self.track_previews.iter_mut().zip(&self.tracks) {
+            if preview.track != *track {
+                *preview = TrackPreview::new(rl, thread, data, *track);
             }
+        }
 
-            // Track selection
-            for (i, rect) in self.track_rects.iter().enumerate() {
-                if check_collision_point_rect(&click, rect) {
-                    data.selected_track = Some(match i {
-                        0 => TrackChoice::Track1,
-                        1 => TrackChoice::Track2,
-                        2 => TrackChoice::Track3,
-                        _ => TrackChoice::Track4,
-                    });
-                    println!("Track {} selected", i + 1);
-                }
+        // Rows: player count, the four race options (one a row so they keep
+        // left/right), tracks, cars, player tabs, play, back
+        let rows = [
+            self.count_list.visible,
+            1,
+            1,
+            1,
+            1,
+            self.track_list.visible,
+            self.car_list.visible,
+            data.players.len(),
+            1,
+            1,
+        ];
+        self.focus.navigate(&nav, &rows);
+        self.count_list.focus(self.focus.in_row(0));
+        self.mode_slider.focused = self.focus.is(1, 0);
+        self.laps_slider.focused = self.focus.is(2, 0);
+        self.opponents_slider.focused = self.focus.is(3, 0);
+        self.difficulty_slider.focused = self.focus.is(4, 0);
+        self.track_list.focus(self.focus.in_row(5));
+        self.car_list.focus(self.focus.in_row(6));
+        self.player_list.focus(self.focus.in_row(7));
+        self.play_button.focused = self.focus.is(8, 0);
+        self.back_button.focused = self.focus.is(9, 0);
+
+        let count_clicked = self.count_list.update(rl, &nav);
+        let mode_changed = self.mode_slider.update(rl, &nav);
+        let laps_changed = self.laps_slider.update(rl, &nav);
+        let opponents_changed = self.opponents_slider.update(rl, &nav);
+        let difficulty_changed = self.difficulty_slider.update(rl, &nav);
+        let track_clicked = self.track_list.update(rl, &nav);
+        let car_clicked = self.car_list.update(rl, &nav);
+        let player_clicked = self.player_list.update(rl, &nav);
+        let play_clicked = self.play_button.update(rl, &nav);
+        let back_clicked = self.back_button.update(rl, &nav);
+
+        // Sliders click on each step from the keys or a pad, not all
+        // through a drag
+        let stepped = nav.left || nav.right;
+        let slider_changed =
+            mode_changed || laps_changed || opponents_changed || difficulty_changed;
+        let list_clicked = [count_clicked, track_clicked, car_clicked, player_clicked]
+            .iter()
+            .any(Option::is_some);
+        if list_clicked || play_clicked || back_clicked || (stepped && slider_changed) {
+            data.play_click();
+        }
+
+        if back_clicked {
+            return SceneSwitch::Pop;
+        }
+
+        // Play button, only enabled once everything needed is picked
+        if play_clicked && data.mode == GameMode::Championship {
+            // Carry on with a cup in progress, or start one with this setup
+            if data.championship.is_none() {
+                data.championship = Some(Championship::new(data));
             }
+            let standings =
+                ChampionshipScene::new(rl, thread, data.screen_width, data.screen_height);
+            return SceneSwitch::Push(Box::new(standings));
+        }
 
-            // Car selection
-            for (i, rect) in self.car_rects.iter().enumerate() {
-                if check_collision_point_rect(&click, rect) {
-                    data.selected_car = Some(match i {
-                        0 => CarChoice::Car1,
-                        1 => CarChoice::Car2,
-                        2 => CarChoice::Car3,
-                        _ => CarChoice::Car4,
-                    });
-                    println!("Car {} selected", i + 1);
-                }
+        if play_clicked {
+            println!("Play button clicked");
+            return SceneSwitch::Push(Box::new(GameScene::new(
+                rl,
+                thread,
+                Vector2::new(100.0, 100.0),
+                90.0,
+            )));
+        }
+
+        if mode_changed {
+            data.mode = GameMode::ALL[self.mode_slider.value as usize];
+            println!("{} mode", data.mode.name());
+        }
+        if laps_changed && data.mode == GameMode::Elimination {
+            data.knockout = Knockout::ALL[self.laps_slider.value as usize];
+        } else if laps_changed {
+            data.laps = self.laps_slider.value as u32;
+        }
+        if opponents_changed {
+            data.set_opponents(self.opponents_slider.value as usize);
+        }
+        if difficulty_changed {
+            data.difficulty = Difficulty::ALL[self.difficulty_slider.value as usize];
+            println!("{} opponents", data.difficulty.name());
+        }
+
+        // Track selection, picking the random track again rolls a new one
+        if let Some(i) = track_clicked {
+            if let TrackChoice::Random(_) = self.tracks[i]
+                && data.selected_track == Some(self.tracks[i])
+            {
+                self.tracks[i] = TrackChoice::Random(TrackSeed::random());
+            }
+            data.selected_track = Some(self.tracks[i]);
+            println!("{} selected", track_name(&self.tracks[i]));
+        }
+
+        // Car selection
+        if let Some(i) = car_clicked {
+            data.players[self.editing_player].car = Some(CarChoice::ALL[i]);
+            println!(
+                "Car {} selected for player {}",
+                i + 1,
+                self.editing_player + 1
+            );
+        }
+
+        // Player count
+        if let Some(i) = count_clicked {
+            data.set_player_count(i + 1);
+            self.editing_player = self.editing_player.min(i);
+            println!("{} players", i + 1);
+        }
+
+        // Player tabs, clicking the open tab again changes its controls
+        if let Some(i) = player_clicked.filter(|&i| i < data.players.len()) {
+            if self.editing_player == i {
+                let player = &mut data.players[i];
+                player.control = Some(next_control(player.control));
+                println!(
+                    "Player {} controls: {}",
+                    i + 1,
+                    control_name(player.control)
+                );
+            } else {
+                self.editing_player = i;
             }
         }
 
@@ -130,128 +342,347 @@ impl Scene for SelectScene {
         // Clear background
         d.clear_background(Color::WHITESMOKE);
 
-        let screen_w = data.screen_width as f32;
-        let screen_center_x = screen_w / 2.0;
-
         // Draw background texture
         if let Some(texture) = &self.background_texture {
-            let tex_w = texture.width as f32;
-            let tex_h = texture.height as f32;
-            let win_w = data.screen_width as f32;
-            let win_h = data.screen_height as f32;
-
-            let scale = (win_w / tex_w).max(win_h / tex_h);
-            let dest_w = tex_w * scale;
-            let dest_h = tex_h * scale;
-            let dest_x = (win_w - dest_w) / 2.0;
-            let dest_y = (win_h - dest_h) / 2.0;
-
-            d.draw_texture_pro(
+            draw_background(
+                d,
                 texture,
-                Rectangle::new(0.0, 0.0, tex_w, tex_h),
-                Rectangle::new(dest_x, dest_y, dest_w, dest_h),
-                Vector2::zero(),
-                0.0,
-                Color::WHITE,
+                data.screen_width as f32,
+                data.screen_height as f32,
             );
         }
 
-        let default_color = Color::BURLYWOOD;
-        let selected_color = Color::LEMONCHIFFON;
-
-        // Track buttons
-        let track_title = "Track Select";
-        let track_title_size = 40;
-        let track_title_width = d.measure_text(track_title, track_title_size);
-        d.draw_text(
-            track_title,
-            (screen_center_x - track_title_width as f32 / 2.0) as i32,
-            170,
-            track_title_size,
-            Color::BLACK,
-        );
+        self.count_list.draw(d);
+        self.mode_slider.draw(d);
+        self.laps_slider.draw(d);
+        self.opponents_slider.draw(d);
+        self.difficulty_slider.draw(d);
 
-        for (i, rect) in self.track_rects.iter().enumerate() {
-            let track = match i {
-                0 => TrackChoice::Track1,
-                1 => TrackChoice::Track2,
-                2 => TrackChoice::Track3,
-                _ => TrackChoice::Track4,
-            };
-            let color = if data.selected_track == Some(track) {
-                selected_color
-            } else {
-                default_color
+        self.track_title.draw(d);
+        for (button, preview) in self.track_list.items.iter().zip(&self.track_previews) {
+            button.draw_with_image(d, &preview.texture, 0.0);
+        }
+
+        self.car_title.draw(d);
+        for (button, sprite) in self.car_list.items.iter().zip(&self.car_sprites) {
+            // The artwork faces up, turned to lie across the button
+            button.draw_with_image(d, sprite, 90.0);
+        }
+
+        self.player_list.draw(d);
+        self.play_button.draw(d);
+        self.back_button.draw(d);
+
+        // Details of whatever is under the mouse or focused, over the rest
+        let screen = Vector2::new(data.screen_width as f32, data.screen_height as f32);
+        if let Some(i) = self.track_list.highlighted()
+            && let Some(preview) = self.track_previews.get(i)
+        {
+            let name = track_name(&preview.track);
+            let best = |time: Option<f32>| time.map_or("--:--.---".to_string(), format_time);
+            let mut lines = vec![
+                Detail::Text(format!("Lap length {}", format_distance(preview.length))),
+                Detail::Text(format!("Best lap {}", best(data.records.best_lap(&name)))),
+                // For the laps and mode picked above
+                Detail::Text(format!(
+                    "{} lap race {}",
+                    data.laps,
+                    best(data.records.best_race(&name, data.laps, data.mode))
+                )),
+            ];
+            if data.mode == GameMode::TimeTrial {
+                for class in CarClass::ALL {
+                    let medal = data.records.medal(&name, class);
+                    lines.push(Detail::Text(format!(
+                        "Class {} medal {}",
+                        class.name(),
+                        medal.map_or("--", |medal| medal.name())
+                    )));
+                }
+            }
+            if data.mode == GameMode::Drift {
+                let score = data.records.best_drift(&name);
+                lines.push(Detail::Text(format!(
+                    "Best drift {}",
+                    score.map_or("--".to_string(), |score| (score.round() as i32).to_string())
+                )));
+            }
+            if data.mode == GameMode::Rush {
+                let gates = data.records.best_rush(&name);
+                lines.push(Detail::Text(format!(
+                    "Best rush {}",
+                    gates.map_or("--".to_string(), |gates| format!("{} checkpoints", gates))
+                )));
+            }
+            draw_detail(d, self.track_list.items[i].rect, screen, &name, &lines);
+        } else if let Some(i) = self.car_list.highlighted() {
+            let car = CarChoice::ALL[i];
+            let stats = data.car_table.stats(&car);
+            let all = CarChoice::ALL.map(|car| data.car_table.stats(&car));
+            let share = |value: f32, stat: fn(&CarStats) -> f32| {
+                value / all.iter().map(stat).fold(f32::EPSILON, f32::max)
             };
-            d.draw_rectangle_rounded(*rect, 0.4, 12, color);
-
-            let label = format!("Track {}", i + 1);
-            let text_w = d.measure_text(&label, 20);
-            d.draw_text(
-                &label,
-                (rect.x + (rect.width - text_w as f32) / 2.0) as i32,
-                (rect.y + (rect.height - 20.0) / 2.0) as i32,
-                20,
-                Color::BLACK,
+            let lines = [
+                Detail::Bar("Speed", share(stats.max_speed, |s| s.max_speed)),
+                Detail::Bar("Acceleration", share(stats.accel_rate, |s| s.accel_rate)),
+                Detail::Bar("Handling", share(stats.handling, |s| s.handling)),
+                Detail::Bar("Braking", share(stats.brake_rate, |s| s.brake_rate)),
+            ];
+            draw_detail(
+                d,
+                self.car_list.items[i].rect,
+                screen,
+                car_name(&car),
+                &lines,
             );
         }
+    }
 
-        // Car buttons
-        let car_title = "Car Select";
-        let car_title_size = 40;
-        let car_title_width = d.measure_text(car_title, car_title_size);
-        d.draw_text(
-            car_title,
-            (screen_center_x - car_title_width as f32 / 2.0) as i32,
-            350,
-            car_title_size,
-            Color::BLACK,
-        );
+    // Thumbnails of edited tracks and cars, while they're loaded
+    fn on_assets_changed(
+        &mut self,
+        rl: &mut RaylibHandle,
+        data: &mut GameData,
+        thread: &RaylibThread,
+        changed: &[String],
+    ) {
+        let was_changed = |path: &str| changed.iter().any(|c| c == path);
 
-        for (i, rect) in self.car_rects.iter().enumerate() {
-            let car = match i {
-                0 => CarChoice::Car1,
-                1 => CarChoice::Car2,
-                2 => CarChoice::Car3,
-                _ => CarChoice::Car4,
+        for preview in &mut self.track_previews {
+            let track = preview.track;
+            let manifest_changed = match track {
+                TrackChoice::Custom(slot) => was_changed(&custom_manifest_path(slot)),
+                _ => false,
             };
-            let color = if data.selected_car == Some(car) {
-                selected_color
-            } else {
-                default_color
-            };
-            d.draw_rectangle_rounded(*rect, 0.4, 12, color);
+            if was_changed(&track_path(&track)) || manifest_changed {
+                *preview = TrackPreview::new(rl, thread, data, track);
+            }
+        }
 
-            let label = format!("Car {}", i + 1);
-            let text_w = d.measure_text(&label, 20);
+        for (sprite, car) in self.car_sprites.iter_mut().zip(CarChoice::ALL) {
+            let [straight, ..] = car_sprite_paths(&car);
+            if was_changed(&straight) {
+                *sprite = data.assets.texture(rl, thread, &straight);
+            }
+        }
+    }
 
-            d.draw_text(
-                &label,
-                (rect.x + (rect.width - text_w as f32) / 2.0) as i32,
-                (rect.y + (rect.height - 20.0) / 2.0) as i32,
-                20,
-                Color::BLACK,
-            );
+    fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {
+        // Dropping the handles lets the cache unload the textures
+        self.track_previews.clear();
+        self.car_sprites.clear();
+    }
+
+    // Mirror the current setup onto the widgets
+    fn update(&mut self, _dt: f32, data: &mut GameData) -> SceneSwitch {
+        self.editing_player = self.editing_player.min(data.players.len() - 1);
+        let editing = data.players[self.editing_player];
+
+        self.count_list.select(Some(data.players.len() - 1));
+
+        let index_of = |found: Option<usize>| found.unwrap_or(0) as f32;
+        self.mode_slider.value = index_of(GameMode::ALL.iter().position(|&m| m == data.mode));
+        // Longer races from the command line show as the most laps
+        if data.mode == GameMode::Elimination {
+            self.laps_slider.label = "Knockout".to_string();
+            self.laps_slider.min = 0.0;
+            self.laps_slider.max = (Knockout::ALL.len() - 1) as f32;
+            self.laps_slider.format = format_knockout;
+            self.laps_slider.value =
+                index_of(Knockout::ALL.iter().position(|&k| k == data.knockout));
+        } else if data.mode == GameMode::Rush {
+            // Runs until the clock runs out
+            self.laps_slider.label = "Laps".to_string();
+            self.laps_slider.min = 0.0;
+            self.laps_slider.max = 0.0;
+            self.laps_slider.format = |_| "Endless".to_string();
+            self.laps_slider.value = 0.0;
+        } else {
+            self.laps_slider.label = "Laps".to_string();
+            self.laps_slider.min = 1.0;
+            self.laps_slider.max = MAX_LAPS as f32;
+            self.laps_slider.format = format_count;
+            self.laps_slider.value = data.laps.min(MAX_LAPS) as f32;
         }
+        // Each extra player takes an opponent's place on the grid, and
+        // modes raced alone pin it at none
+        if data.mode.has_opponents() {
+            self.opponents_slider.max = (MAX_CARS - data.players.len()) as f32;
+            self.opponents_slider.value = data.opponents as f32;
+        } else {
+            self.opponents_slider.max = 0.0;
+            self.opponents_slider.value = 0.0;
+        }
+        self.difficulty_slider.value = index_of(
+            Difficulty::ALL
+                .iter()
+                .position(|&level| level == data.difficulty),
+        );
 
-        // Play button
-        d.draw_rectangle_rounded(self.play_rect, 0.4, 12, Color::BURLYWOOD);
-        let play_text = "Play";
-        let play_text_size = 30;
-        let play_text_width = d.measure_text(play_text, play_text_size);
-
-        d.draw_text(
-            play_text,
-            (self.play_rect.x + (self.play_rect.width - play_text_width as f32) / 2.0) as i32,
-            (self.play_rect.y + (self.play_rect.height - play_text_size as f32) / 2.0) as i32,
-            play_text_size,
-            Color::BLACK,
+        // Keep the seed of a random track picked before, or on the command line
+        if let Some(track @ TrackChoice::Random(_)) = data.selected_track
+            && let Some(last) = self.tracks.last_mut()
+        {
+            *last = track;
+        }
+        for (button, track) in self.track_list.items.iter_mut().zip(&self.tracks) {
+            button.label = track_label(track);
+        }
+
+        self.track_list.select(
+            data.selected_track
+                .and_then(|track| self.tracks.iter().position(|&t| t == track)),
         );
-    }
+        self.car_list.select(
+            editing
+                .car
+                .and_then(|car| CarChoice::ALL.iter().position(|&c| c == car)),
+        );
+
+        self.player_list.visible = data.players.len();
+        self.player_list.select(Some(self.editing_player));
+        for (i, (tab, player)) in self
+            .player_list
+            .items
+            .iter_mut()
+            .zip(&data.players)
+            .enumerate()
+        {
+            tab.label = format!("P{}\n{}", i + 1, control_name(player.control));
+        }
 
-    fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {}
+        // Say what's missing rather than starting a race without it. A cup
+        // in progress brings its own cars.
+        let resuming = data.mode == GameMode::Championship && data.championship.is_some();
+        self.play_button.enabled = resuming || data.ready_to_race();
+        self.play_button.label = if resuming {
+            "Resume Cup".to_string()
+        } else if data.selected_track.is_none() && data.mode != GameMode::Championship {
+            "Pick a Track".to_string()
+        } else if let Some(i) = data.players.iter().position(|p| p.car.is_none()) {
+            if data.players.len() > 1 {
+                format!("Pick a Car for P{}", i + 1)
+            } else {
+                "Pick a Car".to_string()
+            }
+        } else if data.mode == GameMode::Elimination && data.field_size() < 2 {
+            "Add Opponents".to_string()
+        } else if data.mode == GameMode::Championship {
+            "Start Cup".to_string()
+        } else {
+            "Play".to_string()
+        };
 
-    fn update(&mut self, _dt: f32, _data: &mut GameData) -> SceneSwitch {
         SceneSwitch::None
     }
 }
+
+fn format_mode(value: f32) -> String {
+    GameMode::ALL[value as usize].name().to_string()
+}
+
+fn format_difficulty(value: f32) -> String {
+    Difficulty::ALL[value as usize].name().to_string()
+}
+
+fn format_knockout(value: f32) -> String {
+    Knockout::ALL[value as usize].name()
+}
+
+// Random tracks show their seed on a line of its own so it can be shared
+fn track_label(track: &TrackChoice) -> String {
+    match track {
+        TrackChoice::Random(settings) => format!("Random\n#{}", settings.seed),
+        _ => track_name(track),
+    }
+}
+
+// -------- A track's thumbnail and how long a lap is --------
+struct TrackPreview {
+    track: TrackChoice,
+    texture: TextureHandle,
+    // In metres
+    length: f32,
+}
+
+impl TrackPreview {
+    fn new(
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        data: &mut GameData,
+        track: TrackChoice,
+    ) -> Self {
+        // Laid out as it's raced, so the texture is shared with the race
+        let (image, layout) = load_track(&track, VIRTUAL_WIDTH as f32, VIRTUAL_HEIGHT as f32);
+        let texture = data
+            .assets
+            .texture_from_image(rl, thread, &track_path(&track), &image);
+
+        Self {
+            track,
+            texture,
+            length: lap_length(&layout.checkpoints) * METERS_PER_PIXEL,
+        }
+    }
+}
+
+enum Detail {
+    Text(String),
+    // Label and a share of the best car's, 0 to 1
+    Bar(&'static str, f32),
+}
+
+// Under `anchor`, or over it if there's no room below
+fn draw_detail(
+    d: &mut RaylibDrawHandle,
+    anchor: Rectangle,
+    screen: Vector2,
+    title: &str,
+    lines: &[Detail],
+) {
+    let width = 280.0;
+    let line_height = 30.0;
+    let height = 50.0 + lines.len() as f32 * line_height;
+
+    let x = (anchor.x + (anchor.width - width) / 2.0).clamp(10.0, screen.x - width - 10.0);
+    let below = anchor.y + anchor.height + 8.0;
+    let y = if below + height < screen.y {
+        below
+    } else {
+        anchor.y - height - 8.0
+    };
+
+    d.draw_rectangle_rounded(
+        Rectangle::new(x, y, width, height),
+        0.15,
+        8,
+        Color::BLACK.alpha(0.8),
+    );
+    d.draw_text(title, x as i32 + 15, y as i32 + 12, 24, Color::WHITE);
+
+    for (i, line) in lines.iter().enumerate() {
+        let top = y + 48.0 + i as f32 * line_height;
+        match line {
+            Detail::Text(text) => {
+                d.draw_text(text, x as i32 + 15, top as i32, 20, Color::LIGHTGRAY);
+            }
+            Detail::Bar(label, share) => {
+                d.draw_text(label, x as i32 + 15, top as i32, 18, Color::LIGHTGRAY);
+                let bar = Rectangle::new(x + 140.0, top + 3.0, width - 155.0, 12.0);
+                d.draw_rectangle_rec(bar, Color::DARKGRAY);
+                d.draw_rectangle_rec(
+                    Rectangle::new(bar.x, bar.y, bar.width * share.clamp(0.0, 1.0), bar.height),
+                    SELECTED_COLOR,
+                );
+            }
+        }
+    }
+}
+
+fn format_distance(metres: f32) -> String {
+    if metres >= 1000.0 {
+        format!("{:.2} km", metres / 1000.0)
+    } else {
+        format!("{} m", metres.round() as i32)
+    }
+}
@@ -1,28 +1,103 @@
 use raylib::prelude::*;
 
+use crate::assets::TextureHandle;
+use crate::audio::SoundCategory;
+use crate::display::*;
 use crate::game_data::{ControlChoice, GameData};
+use crate::input::read_menu_input;
 use crate::scenes::{Scene, SceneSwitch};
 use crate::select_scene::SelectScene;
-use crate::utils::*;
+use crate::ui::*;
 
 pub struct SettingsScene {
-    background_texture: Option<Texture2D>,
+    background_texture: Option<TextureHandle>,
+
+    title: Label,
+    control_list: List,
+    ghost_toggle: Toggle,
+    // Picks from RESOLUTIONS, WindowMode::ALL and FPS_CAPS by index
+    resolution_slider: Slider,
+    mode_slider: Slider,
+    vsync_toggle: Toggle,
+    fps_slider: Slider,
+    render_scale_slider: Slider,
+    master_slider: Slider,
+    // One per sound category, in SoundCategory::ALL order
+    volume_sliders: Vec<Slider>,
+    mute_toggle: Toggle,
+    play_button: Button,
+    back_button: Button,
+    focus: Focus,
 }
 
 impl SettingsScene {
-    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Self {
-        let background_texture = rl
-            .load_texture(thread, "Assets/settingsBack.png")
-            .expect("Failed to load settings background image");
-
+    pub fn new(_rl: &mut RaylibHandle, _thread: &RaylibThread) -> Self {
         Self {
-            background_texture: Some(background_texture),
+            background_texture: None,
+            title: Label::new("Settings", 60),
+            control_list: List::new(["Controller", "Keyboard"], 30),
+            ghost_toggle: Toggle::new("Ghost Cars", false),
+            resolution_slider: choice_slider("Resolution", RESOLUTIONS.len())
+                .with_format(format_resolution),
+            mode_slider: choice_slider("Window", WindowMode::ALL.len())
+                .with_format(format_window_mode),
+            vsync_toggle: Toggle::new("VSync", true),
+            fps_slider: choice_slider("FPS Cap", FPS_CAPS.len()).with_format(format_fps_cap),
+            render_scale_slider: Slider::new("Race Render Scale", 1.0, 0.25, 1.0).with_step(0.05),
+            master_slider: Slider::new("Master Volume", 0.0, 0.0, 1.0).with_step(0.05),
+            volume_sliders: SoundCategory::ALL
+                .iter()
+                .map(|category| Slider::new(category.name(), 0.0, 0.0, 1.0).with_step(0.05))
+                .collect(),
+            mute_toggle: Toggle::new("Mute", false),
+            play_button: Button::new("Play"),
+            back_button: Button::new("Back").with_font_size(25),
+            focus: Focus::default(),
         }
     }
+
+    fn layout(&mut self, screen_width: i32, screen_height: i32) {
+        let screen_w = screen_width as f32;
+        let center_x = screen_w / 2.0;
+        let top = content_top(screen_height);
+
+        self.title.rect = Rectangle::new(0.0, top + 100.0, screen_w, 60.0);
+
+        // Game and display options on the left, audio on the right
+        let mut column = Layout::column(center_x - 220.0, top + 190.0, 400.0, 46.0, 8.0);
+        self.control_list.layout(&mut column);
+        self.ghost_toggle.rect = column.next_rect();
+
+        column.skip(12.0);
+        self.resolution_slider.rect = column.next_rect();
+        self.mode_slider.rect = column.next_rect();
+        self.vsync_toggle.rect = column.next_rect();
+        self.fps_slider.rect = column.next_rect();
+        self.render_scale_slider.rect = column.next_rect();
+
+        let mut column = Layout::column(center_x + 220.0, top + 190.0, 400.0, 50.0, 8.0);
+        self.master_slider.rect = column.next_rect();
+        for slider in &mut self.volume_sliders {
+            slider.rect = column.next_rect();
+        }
+        self.mute_toggle.rect = column.next_rect();
+
+        self.play_button.rect = Rectangle::new(center_x - 220.0 / 2.0, top + 650.0, 220.0, 50.0);
+
+        self.back_button.rect = Rectangle::new(20.0, 20.0, 120.0, 40.0);
+    }
 }
 
 impl Scene for SettingsScene {
-    fn on_enter(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {}
+    fn on_enter(&mut self, rl: &mut RaylibHandle, data: &mut GameData, thread: &RaylibThread) {
+        self.background_texture = Some(data.assets.texture(rl, thread, "Assets/settingsBack.png"));
+
+        self.layout(data.screen_width, data.screen_height);
+    }
+
+    fn on_resize(&mut self, data: &GameData) {
+        self.layout(data.screen_width, data.screen_height);
+    }
 
     fn handle_input(
         &mut self,
@@ -30,48 +105,126 @@ impl Scene for SettingsScene {
         data: &mut GameData,
         thread: &RaylibThread,
     ) -> SceneSwitch {
-        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
-            let click = rl.get_mouse_position();
+        let nav = read_menu_input(rl);
+        if nav.back {
+            return SceneSwitch::Pop;
+        }
 
-            // Dynamic positions
-            let screen_center_x = data.screen_width as f32 / 2.0;
+        // One widget per row: controller, keyboard, ghost cars, the five
+        // display options, master, each category, mute, play, back
+        let volume_rows = self.volume_sliders.len();
+        let mute_row = 9 + volume_rows;
+        let rows = vec![1; mute_row + 3];
+        self.focus.navigate(&nav, &rows);
+        self.control_list
+            .focus(Some(self.focus.row).filter(|&row| row < 2));
+        self.ghost_toggle.focused = self.focus.is(2, 0);
+        self.resolution_slider.focused = self.focus.is(3, 0);
+        self.mode_slider.focused = self.focus.is(4, 0);
+        self.vsync_toggle.focused = self.focus.is(5, 0);
+        self.fps_slider.focused = self.focus.is(6, 0);
+        self.render_scale_slider.focused = self.focus.is(7, 0);
+        self.master_slider.focused = self.focus.is(8, 0);
+        for (i, slider) in self.volume_sliders.iter_mut().enumerate() {
+            slider.focused = self.focus.is(9 + i, 0);
+        }
+        self.mute_toggle.focused = self.focus.is(mute_row, 0);
+        self.play_button.focused = self.focus.is(mute_row + 1, 0);
+        self.back_button.focused = self.focus.is(mute_row + 2, 0);
 
-            let button_width = 420.0;
-            let button_height = 50.0;
-            let spacing = 20.0;
+        let control_clicked = self.control_list.update(rl, &nav);
+        let ghost_changed = self.ghost_toggle.update(rl, &nav);
+        let display_changed = [
+            self.resolution_slider.update(rl, &nav),
+            self.mode_slider.update(rl, &nav),
+            self.vsync_toggle.update(rl, &nav),
+            self.fps_slider.update(rl, &nav),
+            self.render_scale_slider.update(rl, &nav),
+        ];
+        let master_changed = self.master_slider.update(rl, &nav);
+        let volume_changed: Vec<bool> = self
+            .volume_sliders
+            .iter_mut()
+            .map(|slider| slider.update(rl, &nav))
+            .collect();
+        let mute_changed = self.mute_toggle.update(rl, &nav);
+        let play_clicked = self.play_button.update(rl, &nav);
+        let back_clicked = self.back_button.update(rl, &nav);
 
-            let controller_rect = Rectangle::new(
-                screen_center_x - button_width / 2.0,
-                200.0,
-                button_width,
-                button_height,
-            );
-            let keyboard_rect = Rectangle::new(
-                screen_center_x - button_width / 2.0,
-                controller_rect.y + button_height + spacing,
-                button_width,
-                button_height,
-            );
-            let play_rect = Rectangle::new(
-                screen_center_x - 220.0 / 2.0,
-                keyboard_rect.y + button_height + spacing,
-                220.0,
-                50.0,
-            );
+        // Sliders click on each step from the keys or a pad, not all
+        // through a drag
+        let stepped = nav.left || nav.right;
+        let slider_changed = display_changed.iter().any(|&changed| changed)
+            || master_changed
+            || volume_changed.iter().any(|&changed| changed);
+        let toggled = ghost_changed || display_changed[2] || mute_changed;
+        if control_clicked.is_some()
+            || toggled
+            || play_clicked
+            || back_clicked
+            || (stepped && slider_changed)
+        {
+            data.play_click();
+        }
 
-            // Handle clicks
-            if check_collision_point_rect(&click, &play_rect) {
-                println!("Play button clicked");
-                let select_scene =
-                    SelectScene::new(rl, thread, data.screen_width, data.screen_height);
-                return SceneSwitch::Push(Box::new(select_scene));
-            } else if check_collision_point_rect(&click, &keyboard_rect) {
-                data.selected_control = Some(ControlChoice::Keyboard);
-                println!("Keyboard selected");
-            } else if check_collision_point_rect(&click, &controller_rect) {
-                data.selected_control = Some(ControlChoice::Controller);
+        if back_clicked {
+            return SceneSwitch::Pop;
+        }
+
+        match control_clicked {
+            Some(0) => {
+                data.players[0].control = Some(ControlChoice::Controller(0));
                 println!("Controller selected");
             }
+            Some(_) => {
+                data.players[0].control = Some(ControlChoice::Keyboard);
+                println!("Keyboard selected");
+            }
+            None => {}
+        }
+
+        if ghost_changed {
+            data.ghost_cars = self.ghost_toggle.on;
+            println!("Ghost cars: {}", data.ghost_cars);
+        }
+
+        if display_changed.contains(&true) {
+            let display = &mut data.display;
+            // Only when picked, so a size from the command line isn't lost
+            if display_changed[0] {
+                display.resolution = RESOLUTIONS[self.resolution_slider.value as usize];
+            }
+            display.mode = WindowMode::ALL[self.mode_slider.value as usize];
+            display.vsync = self.vsync_toggle.on;
+            display.fps_cap = FPS_CAPS[self.fps_slider.value as usize];
+            display.render_scale = self.render_scale_slider.value;
+            display.apply_and_save(rl);
+        }
+
+        if master_changed {
+            data.volumes.master = self.master_slider.value;
+        }
+
+        for ((category, slider), changed) in SoundCategory::ALL
+            .iter()
+            .zip(&self.volume_sliders)
+            .zip(volume_changed)
+        {
+            if changed {
+                data.volumes.set(*category, slider.value);
+            }
+        }
+
+        if mute_changed {
+            data.volumes.muted = self.mute_toggle.on;
+            println!("Muted: {}", data.volumes.muted);
+        }
+
+        if play_clicked {
+            println!("Play button clicked");
+            let select_scene = SelectScene::new(rl, thread, data.screen_width, data.screen_height);
+            // Swap rather than stack, so Back from the select screen lands on the menu
+            return SceneSwitch::Replace(Box::new(select_scene));
         }
 
         SceneSwitch::None
@@ -80,122 +233,64 @@ impl Scene for SettingsScene {
     fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
         d.clear_background(Color::KHAKI);
 
-        let screen_center_x = data.screen_width as f32 / 2.0;
-
         // Draw background image
         if let Some(texture) = &self.background_texture {
-            let tex_w = texture.width as f32;
-            let tex_h = texture.height as f32;
-            let win_w = data.screen_width as f32;
-            let win_h = data.screen_height as f32;
-
-            let scale = (win_w / tex_w).max(win_h / tex_h);
-            let dest_w = tex_w * scale;
-            let dest_h = tex_h * scale;
-            let dest_x = (win_w - dest_w) / 2.0;
-            let dest_y = (win_h - dest_h) / 2.0;
-
-            d.draw_texture_pro(
+            draw_background(
+                d,
                 texture,
-                Rectangle::new(0.0, 0.0, tex_w, tex_h),
-                Rectangle::new(dest_x, dest_y, dest_w, dest_h),
-                Vector2::zero(),
-                0.0,
-                Color::WHITE,
+                data.screen_width as f32,
+                data.screen_height as f32,
             );
         }
 
-        // Layout variables
-        let button_width = 420.0;
-        let button_height = 50.0;
-        let spacing = 20.0;
-
-        let controller_rect = Rectangle::new(
-            screen_center_x - button_width / 2.0,
-            200.0,
-            button_width,
-            button_height,
-        );
-        let keyboard_rect = Rectangle::new(
-            screen_center_x - button_width / 2.0,
-            controller_rect.y + button_height + spacing,
-            button_width,
-            button_height,
-        );
-        let play_rect = Rectangle::new(
-            screen_center_x - 220.0 / 2.0,
-            keyboard_rect.y + button_height + spacing,
-            220.0,
-            50.0,
-        );
-
-        // Title text
-        let title = "Settings";
-        let title_size = 60;
-        let title_width = d.measure_text(title, title_size);
-        d.draw_text(
-            title,
-            (screen_center_x - title_width as f32 / 2.0) as i32,
-            100,
-            title_size,
-            Color::BLACK,
-        );
-
-        // Colors
-        let default_color = Color::BURLYWOOD;
-        let selected_color = Color::LEMONCHIFFON;
+        self.title.draw(d);
+        self.control_list.draw(d);
+        self.ghost_toggle.draw(d);
+        self.resolution_slider.draw(d);
+        self.mode_slider.draw(d);
+        self.vsync_toggle.draw(d);
+        self.fps_slider.draw(d);
+        self.render_scale_slider.draw(d);
+        self.master_slider.draw(d);
+        for slider in &self.volume_sliders {
+            slider.draw(d);
+        }
+        self.mute_toggle.draw(d);
+        self.play_button.draw(d);
+        self.back_button.draw(d);
+    }
 
-        let controller_color = if data.selected_control == Some(ControlChoice::Controller) {
-            selected_color
-        } else {
-            default_color
-        };
+    fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {}
 
-        let keyboard_color = if data.selected_control == Some(ControlChoice::Keyboard) {
-            selected_color
-        } else {
-            default_color
+    // Keep the widgets in step with settings changed elsewhere
+    fn update(&mut self, _dt: f32, data: &mut GameData) -> SceneSwitch {
+        let selected = match data.players[0].control {
+            Some(ControlChoice::Controller(_)) => Some(0),
+            Some(ControlChoice::Keyboard) => Some(1),
+            _ => None,
         };
+        self.control_list.select(selected);
+        self.ghost_toggle.on = data.ghost_cars;
 
-        // Draw controller button
-        d.draw_rectangle_rounded(controller_rect, 0.4, 12, controller_color);
-        let controller_text = "Controller";
-        let controller_text_width = d.measure_text(controller_text, 30);
-        d.draw_text(
-            controller_text,
-            (screen_center_x - controller_text_width as f32 / 2.0) as i32,
-            (controller_rect.y + 10.0) as i32,
-            30,
-            Color::BLACK,
-        );
-
-        // Draw keyboard button
-        d.draw_rectangle_rounded(keyboard_rect, 0.4, 12, keyboard_color);
-        let keyboard_text = "Keyboard";
-        let keyboard_text_width = d.measure_text(keyboard_text, 30);
-        d.draw_text(
-            keyboard_text,
-            (screen_center_x - keyboard_text_width as f32 / 2.0) as i32,
-            (keyboard_rect.y + 10.0) as i32,
-            30,
-            Color::BLACK,
+        // A size from the command line that isn't in the list shows as the first
+        let display = &data.display;
+        let index_of = |found: Option<usize>| found.unwrap_or(0) as f32;
+        self.resolution_slider.value = index_of(
+            RESOLUTIONS
+                .iter()
+                .position(|&size| size == display.resolution),
         );
+        self.mode_slider.value = index_of(WindowMode::ALL.iter().position(|&m| m == display.mode));
+        self.vsync_toggle.on = display.vsync;
+        self.fps_slider.value = index_of(FPS_CAPS.iter().position(|&fps| fps == display.fps_cap));
+        self.render_scale_slider.value = display.render_scale;
 
-        // Draw play button
-        d.draw_rectangle_rounded(play_rect, 0.4, 12, Color::BURLYWOOD);
-        let play_text = "Play";
-        let play_text_width = d.measure_text(play_text, 30);
-        d.draw_text(
-            play_text,
-            (screen_center_x - play_text_width as f32 / 2.0) as i32,
-            (play_rect.y + 10.0) as i32,
-            30,
-            Color::BLACK,
-        );
-    }
+        self.master_slider.value = data.volumes.master;
+        for (category, slider) in SoundCategory::ALL.iter().zip(&mut self.volume_sliders) {
+            slider.value = data.volumes.get(*category);
+        }
+        self.mute_toggle.on = data.volumes.muted;
 
-    fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {}
-    fn update(&mut self, _dt: f32, _data: &mut GameData) -> SceneSwitch {
         SceneSwitch::None
     }
 }
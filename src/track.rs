@@ -0,0 +1,482 @@
+use raylib::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::game_data::TrackChoice;
+use crate::track_manifest::custom_image_path;
+use crate::utils::*;
+
+pub const CHECKPOINT_COUNT: usize = 16;
+// Cars start facing left along the top of every bundled track
+pub const SPAWN_DIRECTION: f32 = 180.0;
+// Shorter bright runs are outline specks rather than road
+const MIN_ROAD_RUN: f32 = 8.0;
+// How far a pixel's colour can drift from a surface's and still count
+const SURFACE_TOLERANCE: i32 = 40;
+
+// A gate across the road, laid out in screen space like the track texture
+#[derive(Copy, Clone)]
+pub struct Checkpoint {
+    pub start: Vector2,
+    pub end: Vector2,
+}
+
+impl Checkpoint {
+    pub fn crossed(&self, from: Vector2, to: Vector2) -> bool {
+        segments_intersect(self.start, self.end, from, to)
+    }
+
+    pub fn center(&self) -> Vector2 {
+        (self.start + self.end) * 0.5
+    }
+}
+
+// -------- Ground painted into track images --------
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Surface {
+    Road,
+    OffRoad,
+    Grass,
+    Sand,
+    Ice,
+}
+
+impl Surface {
+    pub const ALL: [Surface; 5] = [
+        Self::Road,
+        Self::OffRoad,
+        Self::Grass,
+        Self::Sand,
+        Self::Ice,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Surface::Road => "Road",
+            Surface::OffRoad => "Off-road",
+            Surface::Grass => "Grass",
+            Surface::Sand => "Sand",
+            Surface::Ice => "Ice",
+        }
+    }
+
+    // The colour the editor paints it with
+    pub fn color(self) -> Color {
+        match self {
+            Surface::Road => Color::WHITE,
+            Surface::OffRoad => Color::BLACK,
+            Surface::Grass => Color::new(70, 150, 60, 255),
+            Surface::Sand => Color::new(225, 190, 120, 255),
+            Surface::Ice => Color::new(160, 215, 245, 255),
+        }
+    }
+
+    // Anything bright is road, so anti-aliased edges of the bundled
+    // tracks keep reading the way they always have
+    pub fn from_color(color: Color) -> Self {
+        if color.r > 200 && color.g > 200 && color.b > 200 {
+            return Surface::Road;
+        }
+
+        [Surface::Grass, Surface::Sand, Surface::Ice]
+            .into_iter()
+            .find(|surface| color_distance(surface.color(), color) < SURFACE_TOLERANCE)
+            .unwrap_or(Surface::OffRoad)
+    }
+
+    // Ice is still road, grass and sand are run-off beside it
+    pub fn is_road(self) -> bool {
+        matches!(self, Surface::Road | Surface::Ice)
+    }
+
+    // Share of the car's steering that still bites
+    pub fn grip(self) -> f32 {
+        match self {
+            Surface::Road | Surface::OffRoad => 1.0,
+            Surface::Grass => 0.8,
+            Surface::Sand => 0.7,
+            Surface::Ice => 0.35,
+        }
+    }
+
+    // Rolling resistance on top of the car's own drag. The bundled tracks
+    // were tuned with nothing slowing cars off the road, so neither does this.
+    pub fn drag(self) -> f32 {
+        match self {
+            Surface::Road | Surface::OffRoad | Surface::Ice => 0.0,
+            Surface::Grass => 1.0,
+            Surface::Sand => 2.5,
+        }
+    }
+}
+
+fn color_distance(a: Color, b: Color) -> i32 {
+    (a.r as i32 - b.r as i32).abs()
+        + (a.g as i32 - b.g as i32).abs()
+        + (a.b as i32 - b.b as i32).abs()
+}
+
+pub fn is_road(color: Color) -> bool {
+    Surface::from_color(color).is_road()
+}
+
+pub fn track_path(track: &TrackChoice) -> String {
+    match track {
+        TrackChoice::Track1 => "Assets/track1.png".to_string(),
+        TrackChoice::Track2 => "Assets/track2.png".to_string(),
+        TrackChoice::Track3 => "Assets/track3.png".to_string(),
+        TrackChoice::Track4 => "Assets/track4.png".to_string(),
+        TrackChoice::Custom(slot) => custom_image_path(*slot),
+        // Never written out, this only names its texture in the asset cache
+        TrackChoice::Random(settings) => format!("<random track {:?}>", settings),
+    }
+}
+
+// Where cars start and the gates they must pass, in screen space
+pub struct TrackLayout {
+    pub spawn: Vector2,
+    pub direction: f32,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+pub fn layout_track(image: &mut Image, screen_w: f32, screen_h: f32) -> TrackLayout {
+    let spawn = find_spawn_pixel(image, screen_w, screen_h);
+    let checkpoints = generate_checkpoints(image, screen_w, screen_h, spawn, SPAWN_DIRECTION);
+
+    TrackLayout {
+        spawn,
+        direction: SPAWN_DIRECTION,
+        checkpoints,
+    }
+}
+
+// -------- Find first white pixel on track --------
+pub fn find_spawn_pixel(image: &mut Image, screen_w: f32, screen_h: f32) -> Vector2 {
+    let (scale, offset) = cover_scale(screen_w, screen_h, image.width as f32, image.height as f32);
+
+    for y in 0..image.height {
+        for x in 0..image.width {
+            if is_road(image.get_color(x, y)) {
+                return Vector2 {
+                    x: (x as f32 * scale) + offset.x,
+                    y: (y as f32 * scale) + offset.y + 35.0,
+                };
+            }
+        }
+    }
+
+    Vector2 {
+        x: screen_w / 2.0,
+        y: screen_h / 2.0,
+    }
+}
+
+// Ground under the screen space `point` of the cover-scaled image
+pub fn surface_at(image: &mut Image, screen_w: f32, screen_h: f32, point: Vector2) -> Surface {
+    let (scale, offset) = cover_scale(screen_w, screen_h, image.width as f32, image.height as f32);
+    let p = (point - offset) / scale;
+
+    let inside =
+        p.x >= 0.0 && p.y >= 0.0 && (p.x as i32) < image.width && (p.y as i32) < image.height;
+    if inside {
+        Surface::from_color(image.get_color(p.x as i32, p.y as i32))
+    } else {
+        Surface::OffRoad
+    }
+}
+
+// Is the screen space `point` on the road of the cover-scaled image
+pub fn road_at(image: &mut Image, screen_w: f32, screen_h: f32, point: Vector2) -> bool {
+    surface_at(image, screen_w, screen_h, point).is_road()
+}
+
+// Distance in image pixels from `point` to the road edge along `dir`
+pub fn road_run(image: &mut Image, point: Vector2, dir: Vector2) -> f32 {
+    let max_dist = (image.width + image.height) as f32;
+    let mut dist = 0.0;
+
+    while dist < max_dist {
+        let p = point + dir * dist;
+        let inside =
+            p.x >= 0.0 && p.y >= 0.0 && (p.x as i32) < image.width && (p.y as i32) < image.height;
+
+        if !inside || !is_road(image.get_color(p.x as i32, p.y as i32)) {
+            break;
+        }
+        dist += 1.0;
+    }
+
+    dist
+}
+
+// Start/finish gate square across the road through `spawn`, in image pixels
+pub fn start_line(image: &mut Image, spawn: Vector2, direction_deg: f32) -> Checkpoint {
+    let rad = direction_deg.to_radians();
+    let side = Vector2::new(-rad.sin(), rad.cos());
+
+    Checkpoint {
+        start: spawn + side * (road_run(image, spawn, side) + 1.0),
+        end: spawn - side * (road_run(image, spawn, -side) + 1.0),
+    }
+}
+
+// -------- Place gates around the loop, starting at the spawn --------
+// Rays are cast from the middle of the road pixels, so this expects the
+// track to wind once around its centre like the bundled ones do.
+pub fn generate_checkpoints(
+    image: &mut Image,
+    screen_w: f32,
+    screen_h: f32,
+    spawn: Vector2,
+    direction_deg: f32,
+) -> Vec<Checkpoint> {
+    let (scale, offset) = cover_scale(screen_w, screen_h, image.width as f32, image.height as f32);
+    let to_screen = |p: Vector2| p * scale + offset;
+    let to_image = |p: Vector2| (p - offset) / scale;
+
+    let Some(centre) = road_centroid(image) else {
+        return Vec::new();
+    };
+
+    let spawn_image = to_image(spawn);
+    let start_angle = (spawn_image.y - centre.y).atan2(spawn_image.x - centre.x);
+
+    // Work out which way round the loop the car is facing
+    let rad = direction_deg.to_radians();
+    let tangent = Vector2::new(-start_angle.sin(), start_angle.cos());
+    let winding = if Vector2::new(rad.cos(), rad.sin()).dot(tangent) >= 0.0 {
+        1.0
+    } else {
+        -1.0
+    };
+
+    let mut checkpoints = Vec::new();
+    for i in 0..CHECKPOINT_COUNT {
+        let angle = start_angle + winding * i as f32 * TAU / CHECKPOINT_COUNT as f32;
+        let ray = Vector2::new(angle.cos(), angle.sin());
+
+        if let Some((enter, exit)) = road_span(image, centre, ray) {
+            checkpoints.push(Checkpoint {
+                start: to_screen(enter),
+                end: to_screen(exit),
+            });
+        }
+    }
+
+    checkpoints
+}
+
+pub(crate) fn road_centroid(image: &mut Image) -> Option<Vector2> {
+    let step = 4;
+    let mut sum = Vector2::zero();
+    let mut count = 0;
+
+    for y in (0..image.height).step_by(step) {
+        for x in (0..image.width).step_by(step) {
+            if is_road(image.get_color(x, y)) {
+                sum += Vector2::new(x as f32, y as f32);
+                count += 1;
+            }
+        }
+    }
+
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count as f32)
+    }
+}
+
+// First stretch of road met walking out from `origin` along `ray`
+pub(crate) fn road_span(
+    image: &mut Image,
+    origin: Vector2,
+    ray: Vector2,
+) -> Option<(Vector2, Vector2)> {
+    let max_dist = (image.width + image.height) as f32;
+    let mut enter = None;
+    let mut dist = 0.0;
+
+    while dist < max_dist {
+        let p = origin + ray * dist;
+        let inside =
+            p.x >= 0.0 && p.y >= 0.0 && (p.x as i32) < image.width && (p.y as i32) < image.height;
+
+        let on_road = inside && is_road(image.get_color(p.x as i32, p.y as i32));
+
+        match (enter, on_road) {
+            (None, true) => enter = Some(p),
+            (Some(start), false) if start.distance_to(p) >= MIN_ROAD_RUN => {
+                return Some((start, p));
+            }
+            (Some(_), false) => enter = None,
+            _ => {}
+        }
+
+        if !inside && enter.is_none() && dist > 0.0 {
+            return None;
+        }
+
+        dist += 1.0;
+    }
+
+    None
+}
+
+// Distance round the loop of gates, in screen pixels. It cuts the
+// corners between gates, so it's a little short of the road's length.
+pub fn lap_length(checkpoints: &[Checkpoint]) -> f32 {
+    let middle = |gate: &Checkpoint| (gate.start + gate.end) / 2.0;
+
+    (0..checkpoints.len())
+        .map(|i| {
+            let next = &checkpoints[(i + 1) % checkpoints.len()];
+            middle(&checkpoints[i]).distance_to(middle(next))
+        })
+        .sum()
+}
+
+// How far round the lap a car is, 0 to 1, going by the gate midpoints
+pub fn lap_fraction(checkpoints: &[Checkpoint], next_checkpoint: usize, position: Vector2) -> f32 {
+    let total = lap_length(checkpoints);
+    if total <= 0.0 {
+        return 0.0;
+    }
+
+    let middle = |gate: &Checkpoint| (gate.start + gate.end) / 2.0;
+    let count = checkpoints.len();
+    let next = next_checkpoint % count;
+    // Gate 0 is the finish, so heading for it means past every other one
+    let last = (next + count - 1) % count;
+
+    let passed: f32 = (0..last)
+        .map(|i| middle(&checkpoints[i]).distance_to(middle(&checkpoints[i + 1])))
+        .sum();
+    let segment = middle(&checkpoints[last]).distance_to(middle(&checkpoints[next]));
+    let along = (segment - position.distance_to(middle(&checkpoints[next]))).clamp(0.0, segment);
+
+    ((passed + along) / total).clamp(0.0, 1.0)
+}
+
+// A gate crossed, timed from the start of the lap
+#[derive(Copy, Clone)]
+pub struct Split {
+    // The car's race time when it crossed
+    pub at: f32,
+    pub lap_time: f32,
+    // Against the same gate on the best lap, negative is quicker
+    pub delta: Option<f32>,
+}
+
+// -------- Lap counting for one car --------
+pub struct LapProgress {
+    pub lap: u32,
+    pub next_checkpoint: usize,
+    pub lap_start: f32,
+    pub last_lap: Option<f32>,
+    pub best_lap: Option<f32>,
+    pub finished: bool,
+
+    // Time into the lap at each gate so far, and at each on the best lap,
+    // the last one being the finish line
+    pub splits: Vec<f32>,
+    pub best_splits: Vec<f32>,
+    pub last_split: Option<Split>,
+}
+
+impl LapProgress {
+    pub fn new() -> Self {
+        Self {
+            lap: 1,
+            // Cars spawn on the start gate, so the first one to hit is the next
+            next_checkpoint: 1,
+            lap_start: 0.0,
+            last_lap: None,
+            best_lap: None,
+            finished: false,
+            splits: Vec::new(),
+            best_splits: Vec::new(),
+            last_split: None,
+        }
+    }
+
+    // Laps driven to the line, `lap` being the one under way until the finish
+    pub fn laps_done(&self) -> u32 {
+        if self.finished {
+            self.lap
+        } else {
+            self.lap - 1
+        }
+    }
+
+    // Gates crossed since the start, over every lap
+    pub fn gates_passed(&self, gate_count: usize) -> usize {
+        (self.lap as usize - 1) * gate_count + self.next_checkpoint - 1
+    }
+
+    fn record_split(&mut self, race_time: f32) {
+        let lap_time = race_time - self.lap_start;
+        let delta = self
+            .best_splits
+            .get(self.splits.len())
+            .map(|best| lap_time - best);
+
+        self.splits.push(lap_time);
+        self.last_split = Some(Split {
+            at: race_time,
+            lap_time,
+            delta,
+        });
+    }
+
+    // Returns true when this move completed a lap
+    pub fn advance(
+        &mut self,
+        checkpoints: &[Checkpoint],
+        from: Vector2,
+        to: Vector2,
+        race_time: f32,
+        total_laps: u32,
+    ) -> bool {
+        if self.finished || checkpoints.len() < 2 {
+            return false;
+        }
+
+        let index = self.next_checkpoint % checkpoints.len();
+        if !checkpoints[index].crossed(from, to) {
+            return false;
+        }
+
+        self.record_split(race_time);
+
+        if index != 0 {
+            self.next_checkpoint += 1;
+            return false;
+        }
+
+        let lap_time = race_time - self.lap_start;
+        self.last_lap = Some(lap_time);
+
+        let splits = std::mem::take(&mut self.splits);
+        if self.best_lap.is_none_or(|best| lap_time < best) {
+            self.best_lap = Some(lap_time);
+            self.best_splits = splits;
+        }
+
+        self.lap_start = race_time;
+        self.next_checkpoint = 1;
+
+        if self.lap >= total_laps {
+            self.finished = true;
+        } else {
+            self.lap += 1;
+        }
+
+        true
+    }
+}
+
+impl Default for LapProgress {
+    fn default() -> Self {
+        Self::new()
+    }
+}
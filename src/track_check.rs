@@ -0,0 +1,490 @@
+use raylib::prelude::*;
+use std::f32::consts::{PI, TAU};
+use std::fmt;
+
+use crate::car::CAR_LENGTH;
+use crate::game_data::MAX_PLAYERS;
+use crate::race::{grid_position, offset_spawn_for_car};
+use crate::track::{
+    CHECKPOINT_COUNT, Checkpoint, TrackLayout, is_road, layout_track, road_at, road_centroid,
+    road_run, road_span,
+};
+use crate::utils::*;
+
+// Image pixels per cell when tracing the road's shape
+const GRID_CELL: i32 = 4;
+// Width is sampled at this many points around the loop
+const WIDTH_SAMPLES: usize = 64;
+const WIDTH_DIRECTIONS: usize = 18;
+// Room for two cars abreast, as on the start grid
+const COMFORTABLE_WIDTH_CARS: f32 = 2.0;
+// Gates longer than this many road widths cut along the road
+const MAX_GATE_SLANT: f32 = 2.5;
+// Screen pixels of non-road that count as the track's edge
+const OUTLINE_WIDTH: i32 = 4;
+// Past this much of the image cut off by the cover scale it looks zoomed in
+const MAX_CROP: f32 = 0.2;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+pub struct TrackIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for TrackIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {}", label, self.message)
+    }
+}
+
+#[derive(Default)]
+pub struct TrackReport {
+    pub issues: Vec<TrackIssue>,
+    // Narrowest stretch of road in screen pixels
+    pub min_width: f32,
+    // Fraction of the image the cover scale cuts off
+    pub crop: f32,
+}
+
+impl TrackReport {
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    fn error(&mut self, message: String) {
+        self.issues.push(TrackIssue {
+            severity: Severity::Error,
+            message,
+        });
+    }
+
+    fn warning(&mut self, message: String) {
+        self.issues.push(TrackIssue {
+            severity: Severity::Warning,
+            message,
+        });
+    }
+}
+
+// -------- Check a track image is fit to race on --------
+// `car_width` is the widest car in screen pixels. Everything is judged the
+// way GameScene shows it: cover-scaled to a `screen_w` x `screen_h` window.
+pub fn check_track(image: &mut Image, screen_w: f32, screen_h: f32, car_width: f32) -> TrackReport {
+    let mut report = TrackReport::default();
+
+    let Some(centre) = road_centroid(image) else {
+        report.error("no road found, road is drawn in white".to_string());
+        return report;
+    };
+
+    check_crop(image, screen_w, screen_h, &mut report);
+    check_loop(image, &mut report);
+
+    let layout = layout_track(image, screen_w, screen_h);
+    check_grid(image, screen_w, screen_h, &layout, car_width, &mut report);
+    check_checkpoints(
+        image,
+        screen_w,
+        screen_h,
+        &layout.checkpoints,
+        car_width,
+        &mut report,
+    );
+    check_width(image, screen_w, screen_h, centre, car_width, &mut report);
+
+    report
+}
+
+// -------- Cover-scale cropping --------
+fn check_crop(image: &mut Image, screen_w: f32, screen_h: f32, report: &mut TrackReport) {
+    let (scale, offset) = cover_scale(screen_w, screen_h, image.width as f32, image.height as f32);
+
+    // The part of the image that ends up on screen
+    let visible = Rectangle::new(
+        -offset.x / scale,
+        -offset.y / scale,
+        screen_w / scale,
+        screen_h / scale,
+    );
+    report.crop = 1.0 - (visible.width * visible.height) / (image.width * image.height) as f32;
+
+    let mut cut = 0;
+    for y in 0..image.height {
+        for x in 0..image.width {
+            let point = Vector2::new(x as f32, y as f32);
+            if !check_collision_point_rect(&point, &visible) && is_road(image.get_color(x, y)) {
+                cut += 1;
+            }
+        }
+    }
+
+    if cut > 0 {
+        report.error(format!(
+            "{} road pixels fall outside a {}x{} window",
+            cut, screen_w, screen_h
+        ));
+    }
+
+    if report.crop > MAX_CROP {
+        report.warning(format!(
+            "{:.0}% of the image is cropped at {}x{}, aim for a {:.2} aspect ratio",
+            report.crop * 100.0,
+            screen_w,
+            screen_h,
+            screen_w / screen_h
+        ));
+    }
+}
+
+// -------- One closed loop --------
+// The road must be one connected piece with exactly one hole in it, the
+// infield. Holes smaller than a car are specks and don't count.
+fn check_loop(image: &mut Image, report: &mut TrackReport) {
+    let grid = Grid::from_image(image);
+
+    let road = grid.regions(false);
+    let Some(track) = road.iter().max_by_key(|region| region.cells.len()) else {
+        return;
+    };
+
+    let speck = (CAR_LENGTH * CAR_LENGTH) as usize / (GRID_CELL * GRID_CELL) as usize;
+
+    let islands = road
+        .iter()
+        .filter(|region| region.cells.len() > speck && region.cells.len() < track.cells.len())
+        .count();
+    if islands > 0 {
+        report.error(format!(
+            "{} separate patches of road, the track must be one piece",
+            islands
+        ));
+    }
+
+    // Everything that isn't the track, diagonals connect so a thin outline
+    // doesn't seal off a corner
+    let mut on_track = vec![false; grid.cells.len()];
+    for &cell in &track.cells {
+        on_track[cell] = true;
+    }
+    let rest = Grid {
+        width: grid.width,
+        height: grid.height,
+        cells: on_track.iter().map(|&t| !t).collect(),
+    };
+
+    let holes = rest
+        .regions(true)
+        .into_iter()
+        .filter(|region| !region.touches_edge && region.cells.len() > speck)
+        .count();
+
+    match holes {
+        0 => report.error("the road doesn't form a loop, there is no infield".to_string()),
+        1 => {}
+        n => report.error(format!(
+            "the road splits around {} islands, it must be a single loop",
+            n - 1
+        )),
+    }
+}
+
+// -------- Start grid --------
+fn check_grid(
+    image: &mut Image,
+    screen_w: f32,
+    screen_h: f32,
+    layout: &TrackLayout,
+    car_width: f32,
+    report: &mut TrackReport,
+) {
+    if !road_at(image, screen_w, screen_h, layout.spawn) {
+        report.error(format!(
+            "spawn point ({:.0}, {:.0}) is off the road",
+            layout.spawn.x, layout.spawn.y
+        ));
+        return;
+    }
+
+    for slot in 0..MAX_PLAYERS {
+        let position = grid_position(layout.spawn, layout.direction, slot, MAX_PLAYERS);
+        let position = offset_spawn_for_car(position, layout.direction);
+
+        if !road_at(image, screen_w, screen_h, position) {
+            report.error(format!(
+                "grid slot {} at ({:.0}, {:.0}) is off the road",
+                slot + 1,
+                position.x,
+                position.y
+            ));
+            continue;
+        }
+
+        let off = car_corners(position, layout.direction, car_width)
+            .iter()
+            .filter(|&&corner| !road_at(image, screen_w, screen_h, corner))
+            .count();
+        if off > 0 {
+            report.warning(format!(
+                "grid slot {} at ({:.0}, {:.0}) overhangs the road edge",
+                slot + 1,
+                position.x,
+                position.y
+            ));
+        }
+    }
+}
+
+fn car_corners(position: Vector2, direction_deg: f32, car_width: f32) -> [Vector2; 4] {
+    let rad = direction_deg.to_radians();
+    let forward = Vector2::new(rad.cos(), rad.sin()) * (CAR_LENGTH / 2.0);
+    let side = Vector2::new(-rad.sin(), rad.cos()) * (car_width / 2.0);
+
+    [
+        position + forward + side,
+        position + forward - side,
+        position - forward + side,
+        position - forward - side,
+    ]
+}
+
+// -------- Checkpoints --------
+// Every gate must exist, reach from one edge of the road to the other and
+// run across it rather than along it
+fn check_checkpoints(
+    image: &mut Image,
+    screen_w: f32,
+    screen_h: f32,
+    checkpoints: &[Checkpoint],
+    car_width: f32,
+    report: &mut TrackReport,
+) {
+    if checkpoints.len() < CHECKPOINT_COUNT {
+        report.error(format!(
+            "only {} of {} checkpoints found road, the track must circle its centre",
+            checkpoints.len(),
+            CHECKPOINT_COUNT
+        ));
+    }
+
+    let (scale, offset) = cover_scale(screen_w, screen_h, image.width as f32, image.height as f32);
+
+    for (i, gate) in checkpoints.iter().enumerate() {
+        let along = gate.end - gate.start;
+        let length = along.length();
+        let dir = along / length.max(1.0);
+
+        // Anti-aliased specks can end a gate early, only a gap a car fits
+        // through matters
+        let gap = road_past(image, screen_w, screen_h, gate.start, -dir, car_width).max(road_past(
+            image, screen_w, screen_h, gate.end, dir, car_width,
+        ));
+        if gap >= car_width {
+            report.error(format!(
+                "checkpoint {} leaves a {:.0}px gap to the road edge",
+                i + 1,
+                gap
+            ));
+        }
+
+        let width = road_width_at(image, (gate.center() - offset) / scale) * scale;
+        if length > width * MAX_GATE_SLANT {
+            report.warning(format!(
+                "checkpoint {} runs along the road, {:.0}px long where it is {:.0}px wide",
+                i + 1,
+                length,
+                width
+            ));
+        }
+    }
+}
+
+// Longest run of road within two car widths past a gate end, stopping at
+// the outline
+fn road_past(
+    image: &mut Image,
+    screen_w: f32,
+    screen_h: f32,
+    from: Vector2,
+    dir: Vector2,
+    car_width: f32,
+) -> f32 {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut dark = 0;
+
+    for step in 1..=(car_width * 2.0) as i32 {
+        if road_at(image, screen_w, screen_h, from + dir * step as f32) {
+            run += 1;
+            dark = 0;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+            dark += 1;
+            if dark >= OUTLINE_WIDTH {
+                break;
+            }
+        }
+    }
+
+    longest as f32
+}
+
+// -------- Road width --------
+fn check_width(
+    image: &mut Image,
+    screen_w: f32,
+    screen_h: f32,
+    centre: Vector2,
+    car_width: f32,
+    report: &mut TrackReport,
+) {
+    let (scale, offset) = cover_scale(screen_w, screen_h, image.width as f32, image.height as f32);
+
+    let mut narrowest: Option<(f32, Vector2)> = None;
+    for i in 0..WIDTH_SAMPLES {
+        let angle = i as f32 * TAU / WIDTH_SAMPLES as f32;
+        let ray = Vector2::new(angle.cos(), angle.sin());
+
+        let Some((enter, exit)) = road_span(image, centre, ray) else {
+            continue;
+        };
+
+        let middle = (enter + exit) * 0.5;
+        let width = road_width_at(image, middle) * scale;
+        if narrowest.is_none_or(|(min, _)| width < min) {
+            narrowest = Some((width, middle * scale + offset));
+        }
+    }
+
+    let Some((width, at)) = narrowest else {
+        return;
+    };
+    report.min_width = width;
+
+    if width < car_width {
+        report.error(format!(
+            "road is {:.0}px wide near ({:.0}, {:.0}), narrower than a {:.0}px car",
+            width, at.x, at.y, car_width
+        ));
+    } else if width < car_width * COMFORTABLE_WIDTH_CARS {
+        report.warning(format!(
+            "road is {:.0}px wide near ({:.0}, {:.0}), too narrow for two cars abreast",
+            width, at.x, at.y
+        ));
+    }
+}
+
+// Shortest line across the road through `point`, in image pixels
+fn road_width_at(image: &mut Image, point: Vector2) -> f32 {
+    (0..WIDTH_DIRECTIONS)
+        .map(|i| {
+            let angle = i as f32 * PI / WIDTH_DIRECTIONS as f32;
+            let dir = Vector2::new(angle.cos(), angle.sin());
+            road_run(image, point, dir) + road_run(image, point, -dir)
+        })
+        .fold(f32::INFINITY, f32::min)
+}
+
+// -------- Coarse road map for tracing connected regions --------
+struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+}
+
+struct Region {
+    cells: Vec<usize>,
+    touches_edge: bool,
+}
+
+impl Grid {
+    fn from_image(image: &mut Image) -> Self {
+        let width = (image.width / GRID_CELL) as usize;
+        let height = (image.height / GRID_CELL) as usize;
+
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = image.get_color(x as i32 * GRID_CELL, y as i32 * GRID_CELL);
+                cells.push(is_road(color));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    // Flood fills the set cells, optionally through diagonals
+    fn regions(&self, diagonal: bool) -> Vec<Region> {
+        let mut seen = vec![false; self.cells.len()];
+        let mut regions = Vec::new();
+
+        for first in 0..self.cells.len() {
+            if seen[first] || !self.cells[first] {
+                continue;
+            }
+
+            let mut region = Region {
+                cells: Vec::new(),
+                touches_edge: false,
+            };
+            let mut stack = vec![first];
+            seen[first] = true;
+
+            while let Some(cell) = stack.pop() {
+                region.cells.push(cell);
+
+                let (x, y) = ((cell % self.width) as i32, (cell / self.width) as i32);
+                if x == 0 || y == 0 || x as usize == self.width - 1 || y as usize == self.height - 1
+                {
+                    region.touches_edge = true;
+                }
+
+                for (dx, dy) in neighbours(diagonal) {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx as usize >= self.width || ny as usize >= self.height {
+                        continue;
+                    }
+
+                    let next = ny as usize * self.width + nx as usize;
+                    if !seen[next] && self.cells[next] {
+                        seen[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+
+            regions.push(region);
+        }
+
+        regions
+    }
+}
+
+fn neighbours(diagonal: bool) -> &'static [(i32, i32)] {
+    const SIDES: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+    const ALL: [(i32, i32); 8] = [
+        (1, 0),
+        (-1, 0),
+        (0, 1),
+        (0, -1),
+        (1, 1),
+        (1, -1),
+        (-1, 1),
+        (-1, -1),
+    ];
+
+    if diagonal { &ALL } else { &SIDES }
+}
@@ -0,0 +1,641 @@
+use raylib::prelude::*;
+use std::ffi::CString;
+
+use crate::assets::{AssetError, load_image, report};
+use crate::car::{CAR_LENGTH, CAR_WIDTH};
+use crate::display::{VIRTUAL_HEIGHT, VIRTUAL_WIDTH, letterbox};
+use crate::game_data::{GameData, MAX_PLAYERS};
+use crate::game_scene::GameScene;
+use crate::input::read_menu_input;
+use crate::medals::MedalTargets;
+use crate::race::{grid_position, offset_spawn_for_car};
+use crate::scenes::{Scene, SceneSwitch};
+use crate::track::{Checkpoint, Surface, start_line};
+use crate::track_manifest::{
+    CUSTOM_TRACK_DIR, CUSTOM_TRACK_SLOTS, TrackManifest, custom_image_path, custom_manifest_path,
+};
+use crate::ui::*;
+use crate::utils::*;
+
+const TOOLBAR_WIDTH: f32 = 180.0;
+// Screen pixels, how close a click must be to grab a point or gate
+const PICK_DISTANCE: f32 = 15.0;
+// Shorter drags are treated as clicks
+const MIN_DRAG: f32 = 10.0;
+const SPLINE_STEPS: usize = 24;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Tool {
+    Brush(Surface),
+    Spline,
+    StartGrid,
+    Checkpoints,
+}
+
+const TOOLS: [Tool; 8] = [
+    Tool::Brush(Surface::Road),
+    Tool::Brush(Surface::OffRoad),
+    Tool::Brush(Surface::Grass),
+    Tool::Brush(Surface::Sand),
+    Tool::Brush(Surface::Ice),
+    Tool::Spline,
+    Tool::StartGrid,
+    Tool::Checkpoints,
+];
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Brush(surface) => surface.name(),
+            Tool::Spline => "Spline",
+            Tool::StartGrid => "Start Grid",
+            Tool::Checkpoints => "Checkpoints",
+        }
+    }
+
+    fn hint(self) -> &'static str {
+        match self {
+            Tool::Brush(_) => "Drag to paint",
+            Tool::Spline => {
+                "Click to add points, click the first to close the loop, right click to finish"
+            }
+            Tool::StartGrid => "Click to place the grid, drag to point it the way to race",
+            Tool::Checkpoints => {
+                "Drag across the road for each gate in order, right click removes one"
+            }
+        }
+    }
+}
+
+// -------- Paint a track and its start, gates and direction --------
+// The canvas is the track image itself, drawn with the same cover scale
+// as GameScene, so what is painted here is what gets raced on.
+pub struct TrackEditorScene {
+    canvas: Image,
+    canvas_texture: Option<Texture2D>,
+    canvas_changed: bool,
+
+    // Image pixels, like the saved manifest
+    spawn: Option<Vector2>,
+    direction: f32,
+    checkpoints: Vec<Checkpoint>,
+    spline: Vec<Vector2>,
+    // Laid across whatever road is under the spawn, so kept up to date
+    start: Option<Checkpoint>,
+    // Medal times written into the slot's manifest by hand, kept on save
+    targets: MedalTargets,
+
+    tool: Tool,
+    drag_start: Option<Vector2>,
+    last_paint: Option<Vector2>,
+    slot: u8,
+    status: String,
+
+    panel: Panel,
+    tool_list: List,
+    size_slider: Slider,
+    slot_button: Button,
+    test_button: Button,
+    save_button: Button,
+    load_button: Button,
+    clear_button: Button,
+    back_button: Button,
+    focus: Focus,
+}
+
+impl TrackEditorScene {
+    pub fn new(_rl: &mut RaylibHandle, _thread: &RaylibThread, data: &GameData) -> Self {
+        let mut scene = Self {
+            canvas: blank_canvas(),
+            canvas_texture: None,
+            canvas_changed: true,
+            spawn: None,
+            direction: 180.0,
+            checkpoints: Vec::new(),
+            spline: Vec::new(),
+            start: None,
+            targets: MedalTargets::default(),
+            tool: TOOLS[0],
+            drag_start: None,
+            last_paint: None,
+            slot: 1,
+            status: String::new(),
+            panel: Panel::new(Color::BLACK.alpha(0.6)),
+            tool_list: List::new(TOOLS.map(Tool::name), 20),
+            size_slider: Slider::new("Size", 60.0, 10.0, 160.0)
+                .with_step(2.0)
+                .with_format(|value| format!("{}px", value as i32)),
+            slot_button: Button::new("").with_font_size(20),
+            test_button: Button::new("Test Drive").with_font_size(20),
+            save_button: Button::new("Save").with_font_size(20),
+            load_button: Button::new("Load").with_font_size(20),
+            clear_button: Button::new("Clear").with_font_size(20),
+            back_button: Button::new("Back").with_font_size(25),
+            focus: Focus::default(),
+        };
+
+        scene.load_slot();
+        scene.layout(data.screen_height);
+        scene
+    }
+
+    fn layout(&mut self, screen_height: i32) {
+        self.panel.rect = Rectangle::new(0.0, 0.0, TOOLBAR_WIDTH, screen_height as f32);
+        self.back_button.rect = Rectangle::new(20.0, 20.0, 120.0, 40.0);
+
+        let center_x = TOOLBAR_WIDTH / 2.0;
+        let mut column = Layout::column(center_x, 75.0, TOOLBAR_WIDTH - 20.0, 30.0, 5.0);
+        self.tool_list.layout(&mut column);
+
+        column.skip(5.0);
+        let slider = column.next_rect();
+        self.size_slider.rect = Rectangle::new(slider.x, slider.y, slider.width, 50.0);
+        column.skip(25.0);
+
+        self.slot_button.rect = column.next_rect();
+        self.load_button.rect = column.next_rect();
+        self.save_button.rect = column.next_rect();
+        column.skip(10.0);
+        self.test_button.rect = column.next_rect();
+        self.clear_button.rect = column.next_rect();
+    }
+
+    // -------- Canvas and screen space --------
+    // Canvas to window, matching how the track is drawn in a race
+    fn screen_transform(&self, data: &GameData) -> (f32, Vector2) {
+        let (cover, cropped) = cover_scale(
+            VIRTUAL_WIDTH as f32,
+            VIRTUAL_HEIGHT as f32,
+            self.canvas.width as f32,
+            self.canvas.height as f32,
+        );
+        let (scale, offset) = letterbox(data);
+
+        (cover * scale, offset + cropped * scale)
+    }
+
+    fn to_canvas(&self, point: Vector2, data: &GameData) -> Vector2 {
+        let (scale, offset) = self.screen_transform(data);
+        (point - offset) / scale
+    }
+
+    fn to_screen(&self, point: Vector2, data: &GameData) -> Vector2 {
+        let (scale, offset) = self.screen_transform(data);
+        point * scale + offset
+    }
+
+    fn brush_radius(&self, data: &GameData) -> f32 {
+        let (scale, _) = self.screen_transform(data);
+        self.size_slider.value / 2.0 / scale
+    }
+
+    // -------- Saving and loading slots --------
+    fn load_slot(&mut self) {
+        let image_path = custom_image_path(self.slot);
+        let manifest_path = custom_manifest_path(self.slot);
+
+        let (image, manifest) = match (load_image(&image_path), TrackManifest::load(&manifest_path))
+        {
+            (Ok(image), Ok(manifest)) => (image, Some(manifest)),
+            (Err(AssetError::NotFound(_)), _) => {
+                self.status = format!("Custom {} is empty", self.slot);
+                (blank_canvas(), None)
+            }
+            (Err(error), _) | (_, Err(error)) => {
+                report(&error);
+                self.status = format!("Could not load Custom {}", self.slot);
+                return;
+            }
+        };
+
+        self.canvas = image;
+        self.canvas
+            .set_format(PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8);
+        self.canvas_texture = None;
+        self.canvas_changed = true;
+
+        self.spawn = manifest.as_ref().map(|m| m.spawn);
+        self.direction = manifest.as_ref().map_or(180.0, |m| m.direction);
+        self.targets = manifest
+            .as_ref()
+            .map(|m| m.targets.clone())
+            .unwrap_or_default();
+        self.checkpoints = manifest.map(|m| m.checkpoints).unwrap_or_default();
+        self.spline.clear();
+
+        if self.spawn.is_some() {
+            self.status = format!("Loaded Custom {}", self.slot);
+        }
+    }
+
+    fn manifest(&self) -> Option<TrackManifest> {
+        let mut manifest = TrackManifest::new(self.spawn?, self.direction);
+        manifest.checkpoints = self.checkpoints.clone();
+        manifest.targets = self.targets.clone();
+        Some(manifest)
+    }
+
+    fn save_slot(&mut self) -> Result<(), AssetError> {
+        let Some(manifest) = self.manifest() else {
+            return Ok(());
+        };
+
+        let image_path = custom_image_path(self.slot);
+        let failed = |reason: String| AssetError::Invalid {
+            path: image_path.clone(),
+            reason,
+        };
+
+        std::fs::create_dir_all(CUSTOM_TRACK_DIR).map_err(|e| failed(e.to_string()))?;
+
+        // raylib-rs drops ExportImage's result, so call it directly
+        let c_path = CString::new(image_path.as_str()).map_err(|e| failed(e.to_string()))?;
+        if !unsafe { ffi::ExportImage(*self.canvas, c_path.as_ptr()) } {
+            return Err(failed("export failed".to_string()));
+        }
+
+        manifest.save(&custom_manifest_path(self.slot))
+    }
+
+    // -------- Painting --------
+    fn paint(&mut self, from: Vector2, to: Vector2, radius: f32, surface: Surface) {
+        let steps = (from.distance_to(to) / (radius / 4.0).max(1.0))
+            .ceil()
+            .max(1.0) as i32;
+
+        for i in 0..=steps {
+            let point = from + (to - from) * (i as f32 / steps as f32);
+            self.canvas
+                .draw_circle_v(point, radius as i32, surface.color());
+        }
+        self.canvas_changed = true;
+    }
+
+    fn finish_spline(&mut self, closed: bool, radius: f32) {
+        let points = spline_points(&self.spline, closed, SPLINE_STEPS);
+        for pair in points.windows(2) {
+            self.paint(pair[0], pair[1], radius, Surface::Road);
+        }
+        self.spline.clear();
+    }
+
+    fn handle_canvas(&mut self, rl: &RaylibHandle, data: &GameData) {
+        let mouse = rl.get_mouse_position();
+        let over_canvas = mouse.x > TOOLBAR_WIDTH;
+        let point = self.to_canvas(mouse, data);
+        let radius = self.brush_radius(data);
+        let (scale, _) = self.screen_transform(data);
+
+        let left_pressed =
+            over_canvas && rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT);
+        let left_down = rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+        let right_pressed =
+            over_canvas && rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_RIGHT);
+
+        if left_pressed {
+            self.drag_start = Some(point);
+        }
+
+        match self.tool {
+            Tool::Brush(surface) => {
+                if left_down && self.drag_start.is_some() {
+                    let from = self.last_paint.unwrap_or(point);
+                    self.paint(from, point, radius, surface);
+                    self.last_paint = Some(point);
+                } else {
+                    self.last_paint = None;
+                }
+            }
+            Tool::Spline => {
+                let closes = self.spline.len() >= 3
+                    && self.spline[0].distance_to(point) * scale < PICK_DISTANCE;
+
+                if left_pressed && closes {
+                    self.finish_spline(true, radius);
+                } else if left_pressed {
+                    self.spline.push(point);
+                } else if right_pressed && self.spline.len() >= 2 {
+                    self.finish_spline(false, radius);
+                }
+            }
+            Tool::StartGrid => {
+                if left_pressed {
+                    self.spawn = Some(point);
+                }
+                if let (Some(spawn), true) = (self.spawn, left_down && self.drag_start.is_some()) {
+                    let towards = point - spawn;
+                    if towards.length() * scale > MIN_DRAG {
+                        self.direction = towards.y.atan2(towards.x).to_degrees();
+                    }
+                }
+            }
+            Tool::Checkpoints => {
+                if right_pressed {
+                    let nearest = self
+                        .checkpoints
+                        .iter()
+                        .position(|gate| gate.center().distance_to(point) * scale < PICK_DISTANCE);
+                    if let Some(index) = nearest {
+                        self.checkpoints.remove(index);
+                    }
+                }
+
+                if let Some(start) = self.drag_start
+                    && !left_down
+                    && start.distance_to(point) * scale > MIN_DRAG
+                {
+                    self.checkpoints.push(Checkpoint { start, end: point });
+                }
+            }
+        }
+
+        if !left_down {
+            self.drag_start = None;
+        }
+    }
+
+    fn upload_canvas(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        if !self.canvas_changed {
+            return;
+        }
+        self.canvas_changed = false;
+
+        if let Some(texture) = &mut self.canvas_texture {
+            let pixels = unsafe {
+                std::slice::from_raw_parts(
+                    self.canvas.data() as *const u8,
+                    self.canvas.get_pixel_data_size(),
+                )
+            };
+            if texture.update_texture(pixels).is_ok() {
+                return;
+            }
+        }
+
+        self.canvas_texture = rl.load_texture_from_image(thread, &self.canvas).ok();
+    }
+
+    // -------- Drawing the start grid and gates over the canvas --------
+    fn draw_overlay(&self, d: &mut RaylibDrawHandle, data: &GameData) {
+        let (scale, _) = self.screen_transform(data);
+        let mouse = d.get_mouse_position();
+
+        // Gates after the start line, numbered in driving order
+        for (i, gate) in self.checkpoints.iter().enumerate() {
+            let start = self.to_screen(gate.start, data);
+            let end = self.to_screen(gate.end, data);
+            d.draw_line_ex(start, end, 3.0, Color::YELLOW);
+
+            let center = (start + end) * 0.5;
+            d.draw_text(
+                &(i + 1).to_string(),
+                center.x as i32 + 6,
+                center.y as i32 - 10,
+                20,
+                Color::YELLOW,
+            );
+        }
+
+        if let (Tool::Checkpoints, Some(start)) = (self.tool, self.drag_start) {
+            d.draw_line_ex(self.to_screen(start, data), mouse, 3.0, Color::ORANGE);
+        }
+
+        if let Some(line) = self.start {
+            d.draw_line_ex(
+                self.to_screen(line.start, data),
+                self.to_screen(line.end, data),
+                4.0,
+                Color::RED,
+            );
+        }
+
+        if let Some(spawn) = self.spawn {
+            let spawn = self.to_screen(spawn, data);
+            for slot in 0..MAX_PLAYERS {
+                let position = grid_position(spawn, self.direction, slot, MAX_PLAYERS);
+                let position = offset_spawn_for_car(position, self.direction);
+                d.draw_rectangle_pro(
+                    Rectangle::new(position.x, position.y, CAR_LENGTH, CAR_WIDTH),
+                    Vector2::new(CAR_LENGTH / 2.0, CAR_WIDTH / 2.0),
+                    self.direction,
+                    Color::SKYBLUE.alpha(0.7),
+                );
+            }
+
+            let rad = self.direction.to_radians();
+            let tip = spawn + Vector2::new(rad.cos(), rad.sin()) * 60.0;
+            d.draw_line_ex(spawn, tip, 4.0, Color::LIME);
+            d.draw_circle_v(tip, 6.0, Color::LIME);
+        }
+
+        // The spline so far, following the mouse to the next point
+        if !self.spline.is_empty() {
+            let mut points = self.spline.clone();
+            points.push(self.to_canvas(mouse, data));
+
+            let width = self.size_slider.value;
+            for pair in spline_points(&points, false, SPLINE_STEPS).windows(2) {
+                d.draw_line_ex(
+                    self.to_screen(pair[0], data),
+                    self.to_screen(pair[1], data),
+                    width,
+                    Color::WHITE.alpha(0.4),
+                );
+            }
+            for point in &self.spline {
+                d.draw_circle_v(self.to_screen(*point, data), 6.0, Color::ORANGE);
+            }
+        }
+
+        // Brush outline under the mouse
+        if let Tool::Brush(_) | Tool::Spline = self.tool
+            && mouse.x > TOOLBAR_WIDTH
+        {
+            d.draw_circle_lines(
+                mouse.x as i32,
+                mouse.y as i32,
+                self.brush_radius(data) * scale,
+                Color::ORANGE,
+            );
+        }
+    }
+}
+
+impl Scene for TrackEditorScene {
+    fn on_enter(&mut self, rl: &mut RaylibHandle, _data: &mut GameData, thread: &RaylibThread) {
+        self.canvas_changed = true;
+        self.upload_canvas(rl, thread);
+    }
+
+    fn handle_input(
+        &mut self,
+        rl: &mut RaylibHandle,
+        data: &mut GameData,
+        thread: &RaylibThread,
+    ) -> SceneSwitch {
+        let nav = read_menu_input(rl);
+
+        // Back drops an unfinished spline before it leaves the editor
+        if nav.back {
+            if self.spline.is_empty() {
+                return SceneSwitch::Pop;
+            }
+            self.spline.clear();
+        }
+
+        // Rows: back, the tools, size, slot, load, save, test drive, clear
+        let rows = [1; 15];
+        self.focus.navigate(&nav, &rows);
+        self.back_button.focused = self.focus.is(0, 0);
+        self.tool_list
+            .focus(self.focus.row.checked_sub(1).filter(|&i| i < TOOLS.len()));
+        self.size_slider.focused = self.focus.is(9, 0);
+        self.slot_button.focused = self.focus.is(10, 0);
+        self.load_button.focused = self.focus.is(11, 0);
+        self.save_button.focused = self.focus.is(12, 0);
+        self.test_button.focused = self.focus.is(13, 0);
+        self.clear_button.focused = self.focus.is(14, 0);
+
+        let ready = self.spawn.is_some();
+        self.save_button.enabled = ready;
+        self.test_button.enabled = ready;
+        self.slot_button.label = format!("Slot: Custom {}", self.slot);
+        self.tool_list
+            .select(TOOLS.iter().position(|&tool| tool == self.tool));
+
+        let back_clicked = self.back_button.update(rl, &nav);
+        let tool_clicked = self.tool_list.update(rl, &nav);
+        let size_changed = self.size_slider.update(rl, &nav);
+        let slot_clicked = self.slot_button.update(rl, &nav);
+        let load_clicked = self.load_button.update(rl, &nav);
+        let save_clicked = self.save_button.update(rl, &nav);
+        let test_clicked = self.test_button.update(rl, &nav);
+        let clear_clicked = self.clear_button.update(rl, &nav);
+        let clicked = [
+            back_clicked,
+            tool_clicked.is_some(),
+            slot_clicked,
+            load_clicked,
+            save_clicked,
+            test_clicked,
+            clear_clicked,
+        ];
+        // The size slider only clicks on steps from the keys or a pad
+        if clicked.contains(&true) || (size_changed && (nav.left || nav.right)) {
+            data.play_click();
+        }
+
+        if back_clicked {
+            return SceneSwitch::Pop;
+        }
+
+        if let Some(i) = tool_clicked {
+            self.tool = TOOLS[i];
+            self.spline.clear();
+        }
+
+        if slot_clicked {
+            self.slot = self.slot % CUSTOM_TRACK_SLOTS + 1;
+        }
+
+        if load_clicked {
+            self.load_slot();
+        }
+
+        if save_clicked {
+            self.status = match self.save_slot() {
+                Ok(()) => format!("Saved as Custom {}", self.slot),
+                Err(error) => {
+                    report(&error);
+                    "Save failed, see the log".to_string()
+                }
+            };
+        }
+
+        if clear_clicked {
+            self.canvas = blank_canvas();
+            self.canvas_texture = None;
+            self.canvas_changed = true;
+            self.spawn = None;
+            self.checkpoints.clear();
+            self.spline.clear();
+            self.status = "Cleared".to_string();
+        }
+
+        if test_clicked && let Some(manifest) = self.manifest() {
+            let game_scene = GameScene::test_drive(rl, thread, self.canvas.clone(), manifest);
+            return SceneSwitch::Push(Box::new(game_scene));
+        }
+
+        self.handle_canvas(rl, data);
+        self.upload_canvas(rl, thread);
+
+        self.start = self
+            .spawn
+            .map(|spawn| start_line(&mut self.canvas, spawn, self.direction));
+
+        SceneSwitch::None
+    }
+
+    fn draw(&self, d: &mut RaylibDrawHandle, data: &mut GameData) {
+        d.clear_background(Color::DARKGRAY);
+
+        if let Some(texture) = &self.canvas_texture {
+            let (scale, offset) = self.screen_transform(data);
+            d.draw_texture_ex(texture, offset, 0.0, scale, Color::WHITE);
+        }
+
+        self.draw_overlay(d, data);
+
+        self.panel.draw(d);
+        self.back_button.draw(d);
+        self.tool_list.draw(d);
+        self.size_slider.draw(d);
+        self.slot_button.draw(d);
+        self.load_button.draw(d);
+        self.save_button.draw(d);
+        self.test_button.draw(d);
+        self.clear_button.draw(d);
+
+        // What the current tool does, and the last thing that happened
+        let mut hint = self.tool.hint().to_string();
+        if self.spawn.is_none() {
+            hint += "\nPlace the start grid to save or test drive";
+        }
+        if !self.status.is_empty() {
+            hint = format!("{}\n{}", self.status, hint);
+        }
+
+        let lines = hint.lines().count() as i32;
+        let top = data.screen_height - lines * 24 - 10;
+        d.draw_rectangle(
+            TOOLBAR_WIDTH as i32,
+            top - 5,
+            data.screen_width,
+            lines * 24 + 15,
+            Color::BLACK.alpha(0.6),
+        );
+        for (i, line) in hint.lines().enumerate() {
+            d.draw_text(
+                line,
+                TOOLBAR_WIDTH as i32 + 10,
+                top + i as i32 * 24,
+                20,
+                Color::WHITE,
+            );
+        }
+    }
+
+    fn on_resize(&mut self, data: &GameData) {
+        self.layout(data.screen_height);
+    }
+
+    fn on_exit(&mut self, _rl: &mut RaylibHandle, _data: &mut GameData, _thread: &RaylibThread) {
+        self.canvas_texture = None;
+    }
+}
+
+// Empty off-road canvas the size races are laid out at, so nothing is cropped
+fn blank_canvas() -> Image {
+    Image::gen_image_color(VIRTUAL_WIDTH, VIRTUAL_HEIGHT, Surface::OffRoad.color())
+}
@@ -0,0 +1,250 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use raylib::prelude::*;
+use std::f32::consts::{PI, TAU};
+
+use crate::track::{CHECKPOINT_COUNT, Checkpoint, Surface};
+use crate::track_manifest::TrackManifest;
+use crate::utils::*;
+
+const SPLINE_STEPS: usize = 24;
+// Layouts tried before settling for a plain oval
+const MAX_ATTEMPTS: usize = 60;
+// Grass either side of the road, in image pixels
+const VERGE: f32 = 14.0;
+// Road kept clear behind the spawn for the rest of the grid
+const GRID_LENGTH: f32 = 150.0;
+
+// -------- What a generated track is built from --------
+// The same settings always give the same track, so the seed is all
+// anyone needs to share one that uses the defaults.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct TrackSeed {
+    pub seed: u32,
+    // Share of the window the circuit spreads across, 0 to 1
+    pub length: f32,
+    // 0 is close to an oval, 1 packs in as many corners as fit
+    pub corners: f32,
+    // Road width in image pixels
+    pub width: f32,
+}
+
+impl TrackSeed {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            length: 0.8,
+            corners: 0.5,
+            width: 70.0,
+        }
+    }
+
+    pub fn random() -> Self {
+        Self::new(rand::random_range(0..1_000_000))
+    }
+}
+
+// -------- Build a closed circuit and rasterize it --------
+// The image is `width` x `height` so it covers a window that size exactly,
+// and the manifest is in its pixels like a track saved from the editor.
+pub fn generate_track(settings: &TrackSeed, width: i32, height: i32) -> (Image, TrackManifest) {
+    let mut rng = StdRng::seed_from_u64(settings.seed as u64);
+    let road_width = settings.width.clamp(40.0, 160.0);
+    let size = Vector2::new(width as f32, height as f32);
+
+    let curve = (0..MAX_ATTEMPTS)
+        .map(|_| circuit(&mut rng, settings, road_width, size))
+        .find(|curve| fits(curve, road_width, size))
+        .unwrap_or_else(|| oval(settings, road_width, size));
+
+    // Either way round the loop is as likely
+    let mut curve = even_spacing(&curve, 4.0);
+    if rng.random_bool(0.5) {
+        curve.reverse();
+    }
+
+    let image = rasterize(&curve, road_width, width, height);
+
+    let start = straightest(&curve);
+    curve.rotate_left(start);
+    let spawn = curve[0];
+    let direction = heading(curve[0], curve[1]);
+
+    let mut manifest = TrackManifest::new(spawn, direction);
+    manifest.checkpoints = gates(&curve, road_width);
+
+    (image, manifest)
+}
+
+// Points scattered round an ellipse, sorted by angle so the loop they
+// make never crosses itself, then smoothed
+fn circuit(rng: &mut StdRng, settings: &TrackSeed, road_width: f32, size: Vector2) -> Vec<Vector2> {
+    let corners = settings.corners.clamp(0.0, 1.0);
+    let (centre, radius) = bounds(settings, road_width, size);
+
+    // Wide roads round a short loop only have room for a few bends
+    let perimeter = PI * (radius.x + radius.y);
+    let room = (perimeter / (road_width * 3.0)) as usize;
+    let count = (5 + (corners * 9.0).round() as usize).min(room).max(5);
+
+    // More corners also pull them further in and out
+    let pinch = 0.2 + 0.4 * corners;
+    let spread = TAU / count as f32;
+
+    let points: Vec<Vector2> = (0..count)
+        .map(|i| {
+            let angle = i as f32 * spread + rng.random_range(-0.3..0.3) * spread;
+            let reach = rng.random_range(1.0 - pinch..=1.0);
+            centre + Vector2::new(angle.cos() * radius.x, angle.sin() * radius.y) * reach
+        })
+        .collect();
+
+    spline_points(&points, true, SPLINE_STEPS)
+}
+
+fn oval(settings: &TrackSeed, road_width: f32, size: Vector2) -> Vec<Vector2> {
+    let (centre, radius) = bounds(settings, road_width, size);
+    let count = 64;
+
+    (0..=count)
+        .map(|i| {
+            let angle = i as f32 * TAU / count as f32;
+            centre + Vector2::new(angle.cos() * radius.x, angle.sin() * radius.y)
+        })
+        .collect()
+}
+
+// Centre and radii of the ellipse the circuit is built round
+fn bounds(settings: &TrackSeed, road_width: f32, size: Vector2) -> (Vector2, Vector2) {
+    let margin = road_width / 2.0 + VERGE + 10.0;
+    let length = 0.5 + 0.5 * settings.length.clamp(0.0, 1.0);
+    let radius = (size / 2.0 - Vector2::new(margin, margin)) * length;
+
+    (size / 2.0, radius)
+}
+
+// Inside the window, no bend tighter than the road is wide, and no two
+// stretches of road close enough to merge
+fn fits(curve: &[Vector2], road_width: f32, size: Vector2) -> bool {
+    let margin = road_width / 2.0 + VERGE;
+    let inside = curve.iter().all(|p| {
+        p.x >= margin && p.y >= margin && p.x <= size.x - margin && p.y <= size.y - margin
+    });
+    if !inside {
+        return false;
+    }
+
+    // Along the road from each point, and how far that is in a straight line
+    let points = even_spacing(curve, 8.0);
+    let total = points.len() as f32 * 8.0;
+
+    // Tighter than this and the inside edge of the bend folds over itself
+    let min_radius = road_width / 2.0 + VERGE;
+    let count = points.len();
+    let too_tight = (0..count).any(|i| {
+        let before = points[(i + count - 3) % count];
+        let after = points[(i + 3) % count];
+        turn(before, points[i], after).to_radians() * min_radius > 24.0
+    });
+    if too_tight {
+        return false;
+    }
+
+    let clearance = road_width + VERGE * 2.0;
+
+    points.iter().enumerate().all(|(i, a)| {
+        points.iter().enumerate().skip(i + 1).all(|(j, b)| {
+            let along = (j - i) as f32 * 8.0;
+            along.min(total - along) < clearance * 2.0 || a.distance_to(*b) >= clearance
+        })
+    })
+}
+
+// Points `spacing` apart along `curve`, so distances can be counted in steps
+fn even_spacing(curve: &[Vector2], spacing: f32) -> Vec<Vector2> {
+    let mut points = vec![curve[0]];
+    let mut carried = 0.0;
+
+    for pair in curve.windows(2) {
+        let length = pair[0].distance_to(pair[1]);
+        let mut along = spacing - carried;
+
+        while along <= length {
+            points.push(pair[0] + (pair[1] - pair[0]) * (along / length));
+            along += spacing;
+        }
+        carried = length - (along - spacing);
+    }
+
+    // The curve ends where it started, which would be a duplicate
+    if points.len() > 1 && points[0].distance_to(points[points.len() - 1]) < spacing / 2.0 {
+        points.pop();
+    }
+
+    points
+}
+
+fn rasterize(curve: &[Vector2], road_width: f32, width: i32, height: i32) -> Image {
+    let mut image = Image::gen_image_color(width, height, Surface::OffRoad.color());
+
+    // All the verge first, so it never paints over a bend of road next to it
+    for (surface, radius) in [
+        (Surface::Grass, road_width / 2.0 + VERGE),
+        (Surface::Road, road_width / 2.0),
+    ] {
+        for point in curve {
+            image.draw_circle_v(*point, radius as i32, surface.color());
+        }
+    }
+
+    image
+}
+
+// Index of the point with the least turning over the grid behind it
+fn straightest(curve: &[Vector2]) -> usize {
+    let count = curve.len();
+    let behind = (GRID_LENGTH / 4.0) as usize;
+    let at = |i: isize| curve[i.rem_euclid(count as isize) as usize];
+
+    let turning = |i: usize| {
+        (-(behind as isize)..4)
+            .map(|k| {
+                let i = i as isize + k;
+                turn(at(i - 1), at(i), at(i + 1))
+            })
+            .sum::<f32>()
+    };
+
+    (0..count)
+        .min_by(|&a, &b| turning(a).total_cmp(&turning(b)))
+        .unwrap_or(0)
+}
+
+fn heading(from: Vector2, to: Vector2) -> f32 {
+    (to.y - from.y).atan2(to.x - from.x).to_degrees()
+}
+
+// Degrees the road turns through at `at`, either way
+fn turn(before: Vector2, at: Vector2, after: Vector2) -> f32 {
+    ((heading(at, after) - heading(before, at) + 540.0) % 360.0 - 180.0).abs()
+}
+
+// Evenly round the loop after the start line, square across the road
+fn gates(curve: &[Vector2], road_width: f32) -> Vec<Checkpoint> {
+    let half = road_width / 2.0 + 2.0;
+
+    (1..CHECKPOINT_COUNT)
+        .map(|i| {
+            let index = i * curve.len() / CHECKPOINT_COUNT;
+            let point = curve[index];
+            let next = curve[(index + 1) % curve.len()];
+            let forward = (next - point).normalized();
+            let side = Vector2::new(-forward.y, forward.x);
+
+            Checkpoint {
+                start: point + side * half,
+                end: point - side * half,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,251 @@
+use raylib::prelude::*;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::assets::{AssetError, image_or_placeholder, report};
+use crate::game_data::TrackChoice;
+use crate::medals::MedalTargets;
+use crate::track::{
+    Checkpoint, TrackLayout, generate_checkpoints, layout_track, start_line, track_path,
+};
+use crate::track_generator::generate_track;
+use crate::utils::*;
+
+pub const CUSTOM_TRACK_DIR: &str = "Assets/tracks";
+// Few enough that every track still fits on one row of the select screen
+pub const CUSTOM_TRACK_SLOTS: u8 = 4;
+
+pub fn custom_image_path(slot: u8) -> String {
+    format!("{}/custom{}.png", CUSTOM_TRACK_DIR, slot)
+}
+
+pub fn custom_manifest_path(slot: u8) -> String {
+    format!("{}/custom{}.txt", CUSTOM_TRACK_DIR, slot)
+}
+
+// The bundled tracks followed by every custom slot that has been saved
+pub fn available_tracks() -> Vec<TrackChoice> {
+    let saved = (1..=CUSTOM_TRACK_SLOTS)
+        .filter(|&slot| Path::new(&custom_manifest_path(slot)).is_file())
+        .map(TrackChoice::Custom);
+
+    TrackChoice::ALL.into_iter().chain(saved).collect()
+}
+
+pub fn track_name(track: &TrackChoice) -> String {
+    match track {
+        TrackChoice::Custom(slot) => format!("Custom {}", slot),
+        TrackChoice::Random(settings) => format!("Random #{}", settings.seed),
+        _ => {
+            let number = TrackChoice::ALL
+                .iter()
+                .position(|t| t == track)
+                .unwrap_or(0)
+                + 1;
+            format!("Track {}", number)
+        }
+    }
+}
+
+// Where a track image's cars start and the gates round it. Custom tracks
+// read theirs from the manifest, the bundled ones are worked out.
+pub fn track_layout(
+    track: &TrackChoice,
+    image: &mut Image,
+    screen_w: f32,
+    screen_h: f32,
+) -> TrackLayout {
+    let TrackChoice::Custom(slot) = track else {
+        return layout_track(image, screen_w, screen_h);
+    };
+
+    match TrackManifest::load(&custom_manifest_path(*slot)) {
+        Ok(manifest) => manifest.layout(image, screen_w, screen_h),
+        Err(error) => {
+            report(&error);
+            layout_track(image, screen_w, screen_h)
+        }
+    }
+}
+
+// Medal times sit beside the bundled track images, and in a custom
+// track's manifest. Random tracks have none.
+pub fn track_data_path(track: &TrackChoice) -> Option<String> {
+    match track {
+        TrackChoice::Custom(slot) => Some(custom_manifest_path(*slot)),
+        TrackChoice::Random(_) => None,
+        _ => Some(
+            Path::new(&track_path(track))
+                .with_extension("txt")
+                .to_string_lossy()
+                .into_owned(),
+        ),
+    }
+}
+
+// Empty when the track has no medal times
+pub fn medal_targets(track: &TrackChoice) -> MedalTargets {
+    let Some(path) = track_data_path(track) else {
+        return MedalTargets::default();
+    };
+
+    match MedalTargets::load(&path) {
+        Ok(targets) => targets,
+        Err(AssetError::NotFound(_)) => MedalTargets::default(),
+        Err(error) => {
+            report(&error);
+            MedalTargets::default()
+        }
+    }
+}
+
+// The track's image, read or generated, and its layout in screen space.
+// A missing image falls back to a placeholder with no road on it.
+pub fn load_track(track: &TrackChoice, screen_w: f32, screen_h: f32) -> (Image, TrackLayout) {
+    if let TrackChoice::Random(settings) = track {
+        let (mut image, manifest) = generate_track(settings, screen_w as i32, screen_h as i32);
+        let layout = manifest.layout(&mut image, screen_w, screen_h);
+        return (image, layout);
+    }
+
+    let mut image = image_or_placeholder(&track_path(track));
+    let layout = track_layout(track, &mut image, screen_w, screen_h);
+    (image, layout)
+}
+
+// -------- Start and gates of a custom track --------
+// One entry per line, positions in image pixels:
+//   spawn x y
+//   direction degrees
+//   checkpoint x1 y1 x2 y2
+//   target class gold silver bronze
+// The start line is laid across the road at the spawn, so `checkpoints`
+// are only the gates after it, in the order they are driven.
+#[derive(Clone)]
+pub struct TrackManifest {
+    pub spawn: Vector2,
+    pub direction: f32,
+    pub checkpoints: Vec<Checkpoint>,
+    // Time trial medal times, only set by hand
+    pub targets: MedalTargets,
+}
+
+impl TrackManifest {
+    pub fn new(spawn: Vector2, direction: f32) -> Self {
+        Self {
+            spawn,
+            direction,
+            checkpoints: Vec::new(),
+            targets: MedalTargets::default(),
+        }
+    }
+
+    pub fn load(path: &str) -> Result<Self, AssetError> {
+        let text = fs::read_to_string(path).map_err(|_| AssetError::NotFound(path.to_string()))?;
+        let invalid = |line: usize, reason: &str| AssetError::Invalid {
+            path: path.to_string(),
+            reason: format!("line {}: {}", line + 1, reason),
+        };
+
+        let mut spawn = None;
+        let mut direction = 0.0;
+        let mut checkpoints = Vec::new();
+        let mut targets = MedalTargets::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let key = fields.next().unwrap_or_default();
+
+            if key == "target" {
+                let fields: Vec<&str> = fields.collect();
+                let (class, times) =
+                    MedalTargets::parse_line(&fields).map_err(|reason| invalid(i, &reason))?;
+                targets.set(class, times);
+                continue;
+            }
+
+            let values = fields
+                .map(|field| field.parse::<f32>())
+                .collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| invalid(i, "expected numbers"))?;
+
+            match (key, &values[..]) {
+                ("spawn", &[x, y]) => spawn = Some(Vector2::new(x, y)),
+                ("direction", &[degrees]) => direction = degrees,
+                ("checkpoint", &[x1, y1, x2, y2]) => checkpoints.push(Checkpoint {
+                    start: Vector2::new(x1, y1),
+                    end: Vector2::new(x2, y2),
+                }),
+                ("spawn" | "direction" | "checkpoint", _) => {
+                    return Err(invalid(i, &format!("wrong number of values for {}", key)));
+                }
+                _ => return Err(invalid(i, &format!("unknown entry '{}'", key))),
+            }
+        }
+
+        let spawn = spawn.ok_or(AssetError::Invalid {
+            path: path.to_string(),
+            reason: "no spawn".to_string(),
+        })?;
+
+        Ok(Self {
+            spawn,
+            direction,
+            checkpoints,
+            targets,
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), AssetError> {
+        let mut text = String::from("# Track manifest, positions are in image pixels\n");
+        let _ = writeln!(text, "spawn {:.1} {:.1}", self.spawn.x, self.spawn.y);
+        let _ = writeln!(text, "direction {:.1}", self.direction);
+        for gate in &self.checkpoints {
+            let _ = writeln!(
+                text,
+                "checkpoint {:.1} {:.1} {:.1} {:.1}",
+                gate.start.x, gate.start.y, gate.end.x, gate.end.y
+            );
+        }
+        self.targets.write(&mut text);
+
+        fs::write(path, text).map_err(|e| AssetError::Invalid {
+            path: path.to_string(),
+            reason: e.to_string(),
+        })
+    }
+
+    // Into screen space the way GameScene draws the image
+    pub fn layout(&self, image: &mut Image, screen_w: f32, screen_h: f32) -> TrackLayout {
+        let (scale, offset) =
+            cover_scale(screen_w, screen_h, image.width as f32, image.height as f32);
+        let to_screen = |gate: Checkpoint| Checkpoint {
+            start: gate.start * scale + offset,
+            end: gate.end * scale + offset,
+        };
+
+        let spawn = self.spawn * scale + offset;
+
+        // Without any gates placed fall back to spacing them automatically
+        let checkpoints = if self.checkpoints.is_empty() {
+            generate_checkpoints(image, screen_w, screen_h, spawn, self.direction)
+        } else {
+            std::iter::once(start_line(image, self.spawn, self.direction))
+                .chain(self.checkpoints.iter().copied())
+                .map(to_screen)
+                .collect()
+        };
+
+        TrackLayout {
+            spawn,
+            direction: self.direction,
+            checkpoints,
+        }
+    }
+}
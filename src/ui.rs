@@ -0,0 +1,634 @@
+use raylib::prelude::*;
+
+use crate::input::MenuInput;
+use crate::utils::*;
+
+pub const DEFAULT_COLOR: Color = Color::BURLYWOOD;
+pub const SELECTED_COLOR: Color = Color::LEMONCHIFFON;
+pub const DISABLED_COLOR: Color = Color::LIGHTGRAY;
+pub const TEXT_COLOR: Color = Color::BLACK;
+
+const ROUNDNESS: f32 = 0.4;
+const SEGMENTS: i32 = 12;
+const FOCUS_THICKNESS: f32 = 3.0;
+
+// -------- Mouse state shared by every clickable widget --------
+#[derive(Copy, Clone, Default)]
+struct Interaction {
+    hovered: bool,
+    pressed: bool,
+}
+
+impl Interaction {
+    // Returns true when a click started and ended inside `rect`
+    fn update(&mut self, rl: &RaylibHandle, rect: Rectangle) -> bool {
+        let mouse = rl.get_mouse_position();
+        self.hovered = check_collision_point_rect(&mouse, &rect);
+
+        if self.hovered && rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.pressed = true;
+        }
+
+        let clicked = self.pressed
+            && self.hovered
+            && rl.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT);
+
+        if !rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.pressed = false;
+        }
+
+        clicked
+    }
+
+    fn tint(&self, base: Color) -> Color {
+        if self.pressed && self.hovered {
+            base.brightness(-0.15)
+        } else if self.hovered {
+            base.brightness(0.15)
+        } else {
+            base
+        }
+    }
+}
+
+fn draw_box(d: &mut RaylibDrawHandle, rect: Rectangle, color: Color, focused: bool) {
+    d.draw_rectangle_rounded(rect, ROUNDNESS, SEGMENTS, color);
+
+    if focused {
+        d.draw_rectangle_rounded_lines_ex(rect, ROUNDNESS, SEGMENTS, FOCUS_THICKNESS, TEXT_COLOR);
+    }
+}
+
+// Draw (possibly multi-line) text centred inside `rect`
+pub fn draw_text_centered(
+    d: &mut RaylibDrawHandle,
+    text: &str,
+    rect: Rectangle,
+    font_size: i32,
+    color: Color,
+) {
+    let line_count = text.lines().count().max(1) as f32;
+    let line_height = font_size as f32 + font_size as f32 / 2.0;
+    let text_height = font_size as f32 + (line_count - 1.0) * line_height;
+    let mut y = rect.y + (rect.height - text_height) / 2.0;
+
+    for line in text.lines() {
+        let width = d.measure_text(line, font_size);
+        d.draw_text(
+            line,
+            (rect.x + (rect.width - width as f32) / 2.0) as i32,
+            y as i32,
+            font_size,
+            color,
+        );
+        y += line_height;
+    }
+}
+
+// Scale a texture to cover the whole window, cropping whatever overflows
+pub fn draw_background(d: &mut RaylibDrawHandle, texture: &Texture2D, win_w: f32, win_h: f32) {
+    let tex_w = texture.width as f32;
+    let tex_h = texture.height as f32;
+    let (scale, offset) = cover_scale(win_w, win_h, tex_w, tex_h);
+
+    d.draw_texture_pro(
+        texture,
+        Rectangle::new(0.0, 0.0, tex_w, tex_h),
+        Rectangle::new(offset.x, offset.y, tex_w * scale, tex_h * scale),
+        Vector2::zero(),
+        0.0,
+        Color::WHITE,
+    );
+}
+
+// -------- Layout helper: hands out evenly spaced rectangles --------
+pub struct Layout {
+    cursor: Vector2,
+    size: Vector2,
+    spacing: f32,
+    horizontal: bool,
+}
+
+impl Layout {
+    // A vertical stack of `width` x `height` slots centred on `center_x`
+    pub fn column(center_x: f32, top: f32, width: f32, height: f32, spacing: f32) -> Self {
+        Self {
+            cursor: Vector2::new(center_x - width / 2.0, top),
+            size: Vector2::new(width, height),
+            spacing,
+            horizontal: false,
+        }
+    }
+
+    // `count` slots side by side, the whole row centred on `center_x`
+    pub fn row(
+        center_x: f32,
+        top: f32,
+        count: usize,
+        width: f32,
+        height: f32,
+        spacing: f32,
+    ) -> Self {
+        let row_width = count as f32 * width + count.saturating_sub(1) as f32 * spacing;
+
+        Self {
+            cursor: Vector2::new(center_x - row_width / 2.0, top),
+            size: Vector2::new(width, height),
+            spacing,
+            horizontal: true,
+        }
+    }
+
+    pub fn next_rect(&mut self) -> Rectangle {
+        let rect = Rectangle::new(self.cursor.x, self.cursor.y, self.size.x, self.size.y);
+
+        if self.horizontal {
+            self.cursor.x += self.size.x + self.spacing;
+        } else {
+            self.cursor.y += self.size.y + self.spacing;
+        }
+
+        rect
+    }
+
+    // Leave an extra gap before the next slot
+    pub fn skip(&mut self, amount: f32) {
+        if self.horizontal {
+            self.cursor.x += amount;
+        } else {
+            self.cursor.y += amount;
+        }
+    }
+}
+
+// -------- Focus: which widget keyboard and pad input goes to --------
+// Menus are treated as rows of widgets; up/down moves between rows and
+// left/right between the widgets in a row.
+#[derive(Copy, Clone, Default)]
+pub struct Focus {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Focus {
+    // `rows` holds how many widgets each row has
+    pub fn navigate(&mut self, nav: &MenuInput, rows: &[usize]) {
+        if rows.is_empty() {
+            return;
+        }
+
+        let count = rows.len();
+        if nav.up {
+            self.row = (self.row + count - 1) % count;
+        }
+        if nav.down {
+            self.row = (self.row + 1) % count;
+        }
+        self.row = self.row.min(count - 1);
+
+        // Single widget rows keep left/right for themselves, e.g. sliders
+        let len = rows[self.row].max(1);
+        if len > 1 {
+            if nav.left {
+                self.col = (self.col + len - 1) % len;
+            }
+            if nav.right {
+                self.col = (self.col + 1) % len;
+            }
+        }
+        self.col = self.col.min(len - 1);
+    }
+
+    pub fn is(&self, row: usize, col: usize) -> bool {
+        self.row == row && self.col == col
+    }
+
+    // Column focused in `row`, if any
+    pub fn in_row(&self, row: usize) -> Option<usize> {
+        (self.row == row).then_some(self.col)
+    }
+}
+
+// -------- Button --------
+pub struct Button {
+    pub rect: Rectangle,
+    pub label: String,
+    pub font_size: i32,
+    pub selected: bool,
+    pub enabled: bool,
+    pub focused: bool,
+    interaction: Interaction,
+}
+
+impl Button {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            rect: Rectangle::new(0.0, 0.0, 0.0, 0.0),
+            label: label.into(),
+            font_size: 30,
+            selected: false,
+            enabled: true,
+            focused: false,
+            interaction: Interaction::default(),
+        }
+    }
+
+    pub fn with_font_size(mut self, font_size: i32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    // Returns true when the button was clicked, or confirmed while focused
+    pub fn update(&mut self, rl: &RaylibHandle, nav: &MenuInput) -> bool {
+        let clicked = self.interaction.update(rl, self.rect);
+        (clicked || (self.focused && nav.confirm)) && self.enabled
+    }
+
+    pub fn hovered(&self) -> bool {
+        self.interaction.hovered
+    }
+
+    fn colors(&self) -> (Color, Color) {
+        if !self.enabled {
+            (DISABLED_COLOR, Color::GRAY)
+        } else if self.selected {
+            (self.interaction.tint(SELECTED_COLOR), TEXT_COLOR)
+        } else {
+            (self.interaction.tint(DEFAULT_COLOR), TEXT_COLOR)
+        }
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle) {
+        let (color, text_color) = self.colors();
+
+        draw_box(d, self.rect, color, self.focused);
+        draw_text_centered(d, &self.label, self.rect, self.font_size, text_color);
+    }
+
+    // Label along the bottom with `image` fitted into the space above it,
+    // turned by `rotation` degrees
+    pub fn draw_with_image(&self, d: &mut RaylibDrawHandle, image: &Texture2D, rotation: f32) {
+        let (color, text_color) = self.colors();
+        draw_box(d, self.rect, color, self.focused);
+
+        let lines = self.label.lines().count().max(1) as f32;
+        let label_height = lines * self.font_size as f32 * 1.5;
+        let padding = 8.0;
+        let label = Rectangle::new(
+            self.rect.x,
+            self.rect.y + self.rect.height - label_height - padding / 2.0,
+            self.rect.width,
+            label_height,
+        );
+        draw_text_centered(d, &self.label, label, self.font_size, text_color);
+
+        // Quarter turns swap which way the image is wide
+        let turned = (rotation / 90.0).round() as i32 % 2 != 0;
+        let (width, height) = if turned {
+            (image.height as f32, image.width as f32)
+        } else {
+            (image.width as f32, image.height as f32)
+        };
+
+        let space = Rectangle::new(
+            self.rect.x + padding,
+            self.rect.y + padding,
+            self.rect.width - padding * 2.0,
+            label.y - self.rect.y - padding,
+        );
+        let scale = (space.width / width).min(space.height / height);
+        let center = Vector2::new(space.x + space.width / 2.0, space.y + space.height / 2.0);
+
+        d.draw_texture_pro(
+            image,
+            Rectangle::new(0.0, 0.0, image.width as f32, image.height as f32),
+            Rectangle::new(
+                center.x,
+                center.y,
+                image.width as f32 * scale,
+                image.height as f32 * scale,
+            ),
+            Vector2::new(
+                image.width as f32 * scale / 2.0,
+                image.height as f32 * scale / 2.0,
+            ),
+            rotation,
+            if self.enabled {
+                Color::WHITE
+            } else {
+                Color::GRAY
+            },
+        );
+    }
+}
+
+// -------- Toggle: a button that flips an on/off value --------
+pub struct Toggle {
+    pub rect: Rectangle,
+    pub label: String,
+    pub font_size: i32,
+    pub on: bool,
+    pub focused: bool,
+    interaction: Interaction,
+}
+
+impl Toggle {
+    pub fn new(label: impl Into<String>, on: bool) -> Self {
+        Self {
+            rect: Rectangle::new(0.0, 0.0, 0.0, 0.0),
+            label: label.into(),
+            font_size: 30,
+            on,
+            focused: false,
+            interaction: Interaction::default(),
+        }
+    }
+
+    // Returns true when the value changed this frame
+    pub fn update(&mut self, rl: &RaylibHandle, nav: &MenuInput) -> bool {
+        let clicked = self.interaction.update(rl, self.rect);
+
+        if clicked || (self.focused && nav.confirm) {
+            self.on = !self.on;
+            return true;
+        }
+
+        false
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle) {
+        let base = if self.on {
+            SELECTED_COLOR
+        } else {
+            DEFAULT_COLOR
+        };
+        draw_box(d, self.rect, self.interaction.tint(base), self.focused);
+
+        let text = format!("{}: {}", self.label, if self.on { "On" } else { "Off" });
+        draw_text_centered(d, &text, self.rect, self.font_size, TEXT_COLOR);
+    }
+}
+
+// -------- Slider: drag along the bar to pick a value --------
+pub struct Slider {
+    pub rect: Rectangle,
+    pub label: String,
+    pub font_size: i32,
+    pub value: f32,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+    pub format: fn(f32) -> String,
+    pub focused: bool,
+    interaction: Interaction,
+    dragging: bool,
+}
+
+pub fn format_percent(value: f32) -> String {
+    format!("{}%", (value * 100.0).round() as i32)
+}
+
+pub fn format_count(value: f32) -> String {
+    format!("{}", value.round() as i32)
+}
+
+// A slider that steps through `count` choices, shown by its format
+pub fn choice_slider(label: &str, count: usize) -> Slider {
+    Slider::new(label, 0.0, 0.0, (count - 1) as f32).with_step(1.0)
+}
+
+impl Slider {
+    pub fn new(label: impl Into<String>, value: f32, min: f32, max: f32) -> Self {
+        Self {
+            rect: Rectangle::new(0.0, 0.0, 0.0, 0.0),
+            label: label.into(),
+            font_size: 25,
+            value: value.clamp(min, max),
+            min,
+            max,
+            step: 0.0,
+            format: format_percent,
+            focused: false,
+            interaction: Interaction::default(),
+            dragging: false,
+        }
+    }
+
+    pub fn with_step(mut self, step: f32) -> Self {
+        self.step = step;
+        self
+    }
+
+    pub fn with_format(mut self, format: fn(f32) -> String) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: i32) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    // Nudge by one step (or a tenth of the range)
+    pub fn nudge(&mut self, direction: f32) -> bool {
+        let amount = if self.step > 0.0 {
+            self.step
+        } else {
+            (self.max - self.min) / 10.0
+        };
+        self.set_value(self.value + amount * direction)
+    }
+
+    fn set_value(&mut self, value: f32) -> bool {
+        let mut value = value.clamp(self.min, self.max);
+        if self.step > 0.0 {
+            value = self.min + ((value - self.min) / self.step).round() * self.step;
+        }
+
+        let changed = value != self.value;
+        self.value = value;
+        changed
+    }
+
+    fn bar(&self) -> Rectangle {
+        Rectangle::new(
+            self.rect.x + 15.0,
+            self.rect.y + self.rect.height - 16.0,
+            self.rect.width - 30.0,
+            6.0,
+        )
+    }
+
+    // Returns true when the value changed this frame
+    pub fn update(&mut self, rl: &RaylibHandle, nav: &MenuInput) -> bool {
+        self.interaction.update(rl, self.rect);
+
+        if self.focused && (nav.left || nav.right) {
+            return self.nudge(if nav.right { 1.0 } else { -1.0 });
+        }
+
+        if self.interaction.pressed && self.interaction.hovered {
+            self.dragging = true;
+        }
+        if !rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.dragging = false;
+        }
+
+        if !self.dragging {
+            return false;
+        }
+
+        let bar = self.bar();
+        let t = ((rl.get_mouse_position().x - bar.x) / bar.width).clamp(0.0, 1.0);
+        self.set_value(self.min + t * (self.max - self.min))
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle) {
+        draw_box(
+            d,
+            self.rect,
+            self.interaction.tint(DEFAULT_COLOR),
+            self.focused,
+        );
+
+        let text = format!("{}: {}", self.label, (self.format)(self.value));
+        let text_rect = Rectangle::new(
+            self.rect.x,
+            self.rect.y,
+            self.rect.width,
+            self.rect.height - 16.0,
+        );
+        draw_text_centered(d, &text, text_rect, self.font_size, TEXT_COLOR);
+
+        let bar = self.bar();
+        let t = if self.max > self.min {
+            (self.value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        };
+
+        d.draw_rectangle_rec(bar, Color::BEIGE);
+        d.draw_rectangle_rec(
+            Rectangle::new(bar.x, bar.y, bar.width * t, bar.height),
+            Color::BROWN,
+        );
+        d.draw_circle_v(
+            Vector2::new(bar.x + bar.width * t, bar.y + bar.height / 2.0),
+            8.0,
+            Color::DARKBROWN,
+        );
+    }
+}
+
+// -------- Label: text centred in a rectangle --------
+pub struct Label {
+    pub rect: Rectangle,
+    pub text: String,
+    pub font_size: i32,
+    pub color: Color,
+}
+
+impl Label {
+    pub fn new(text: impl Into<String>, font_size: i32) -> Self {
+        Self {
+            rect: Rectangle::new(0.0, 0.0, 0.0, 0.0),
+            text: text.into(),
+            font_size,
+            color: TEXT_COLOR,
+        }
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle) {
+        draw_text_centered(d, &self.text, self.rect, self.font_size, self.color);
+    }
+}
+
+// -------- List: a row or column of buttons with one selected --------
+pub struct List {
+    pub items: Vec<Button>,
+    // Only the first `visible` items are drawn and clickable
+    pub visible: usize,
+}
+
+impl List {
+    pub fn new<S: Into<String>>(labels: impl IntoIterator<Item = S>, font_size: i32) -> Self {
+        let items: Vec<Button> = labels
+            .into_iter()
+            .map(|label| Button::new(label).with_font_size(font_size))
+            .collect();
+
+        Self {
+            visible: items.len(),
+            items,
+        }
+    }
+
+    pub fn layout(&mut self, layout: &mut Layout) {
+        for item in &mut self.items {
+            item.rect = layout.next_rect();
+        }
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        for (i, item) in self.items.iter_mut().enumerate() {
+            item.selected = index == Some(i);
+        }
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.items.iter().position(|item| item.selected)
+    }
+
+    // The item under the mouse, or else the focused one
+    pub fn highlighted(&self) -> Option<usize> {
+        let visible = &self.items[..self.visible];
+        visible
+            .iter()
+            .position(Button::hovered)
+            .or_else(|| visible.iter().position(|item| item.focused))
+    }
+
+    pub fn focus(&mut self, index: Option<usize>) {
+        for (i, item) in self.items.iter_mut().enumerate() {
+            item.focused = index == Some(i);
+        }
+    }
+
+    // Returns the index of the item clicked this frame
+    pub fn update(&mut self, rl: &RaylibHandle, nav: &MenuInput) -> Option<usize> {
+        let mut clicked = None;
+
+        for (i, item) in self.items.iter_mut().take(self.visible).enumerate() {
+            if item.update(rl, nav) {
+                clicked = Some(i);
+            }
+        }
+
+        clicked
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle) {
+        for item in self.items.iter().take(self.visible) {
+            item.draw(d);
+        }
+    }
+}
+
+// -------- Panel: a plain rounded backdrop --------
+pub struct Panel {
+    pub rect: Rectangle,
+    pub color: Color,
+}
+
+impl Panel {
+    pub fn new(color: Color) -> Self {
+        Self {
+            rect: Rectangle::new(0.0, 0.0, 0.0, 0.0),
+            color,
+        }
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle) {
+        d.draw_rectangle_rounded(self.rect, 0.1, SEGMENTS, self.color);
+    }
+}
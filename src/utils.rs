@@ -5,7 +5,108 @@ pub fn check_collision_point_rect(point: &Vector2, rect: &Rectangle) -> bool {
     let in_x = point.x >= rect.x && point.x <= rect.x + rect.width;
     let in_y = point.y >= rect.y && point.y <= rect.y + rect.height;
 
-    return in_x && in_y;
+    in_x && in_y
+}
+
+// Scale and offset that make a texture cover the whole window, cropping the overflow
+pub fn cover_scale(screen_w: f32, screen_h: f32, tex_w: f32, tex_h: f32) -> (f32, Vector2) {
+    let scale = (screen_w / tex_w).max(screen_h / tex_h);
+    let offset = Vector2::new(
+        (screen_w - tex_w * scale) / 2.0,
+        (screen_h - tex_h * scale) / 2.0,
+    );
+
+    (scale, offset)
+}
+
+// Scale and offset that fit a texture inside the window, leaving bars either side
+pub fn contain_scale(screen_w: f32, screen_h: f32, tex_w: f32, tex_h: f32) -> (f32, Vector2) {
+    let scale = (screen_w / tex_w).min(screen_h / tex_h);
+    let offset = Vector2::new(
+        (screen_w - tex_w * scale) / 2.0,
+        (screen_h - tex_h * scale) / 2.0,
+    );
+
+    (scale, offset)
+}
+
+pub fn segments_intersect(a1: Vector2, a2: Vector2, b1: Vector2, b2: Vector2) -> bool {
+    let cross =
+        |o: Vector2, p: Vector2, q: Vector2| (p.x - o.x) * (q.y - o.y) - (p.y - o.y) * (q.x - o.x);
+
+    let d1 = cross(b1, b2, a1);
+    let d2 = cross(b1, b2, a2);
+    let d3 = cross(a1, a2, b1);
+    let d4 = cross(a1, a2, b2);
+
+    ((d1 > 0.0) != (d2 > 0.0)) && ((d3 > 0.0) != (d4 > 0.0))
+}
+
+pub fn format_time(time: f32) -> String {
+    let minutes = (time / 60.0).floor() as i32;
+    let seconds = (time % 60.0) as i32;
+    let milliseconds = ((time * 1000.0) % 1000.0) as i32;
+
+    format!("{:02}:{:02}.{:03}", minutes, seconds, milliseconds)
+}
+
+// 1st, 2nd, 3rd, 4th...
+pub fn ordinal(place: usize) -> String {
+    let suffix = match place {
+        1 => "st",
+        2 => "nd",
+        3 => "rd",
+        _ => "th",
+    };
+    format!("{}{}", place, suffix)
+}
+
+// -------- Smooth curve through a few points --------
+pub fn catmull_rom(p0: Vector2, p1: Vector2, p2: Vector2, p3: Vector2, t: f32) -> Vector2 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (p1 * 2.0
+        + (p2 - p0) * t
+        + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+        + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+        * 0.5
+}
+
+// `steps` points per span of a curve through every one of `points`,
+// wrapping back to the first when `closed`
+pub fn spline_points(points: &[Vector2], closed: bool, steps: usize) -> Vec<Vector2> {
+    let count = points.len();
+    if count < 2 {
+        return points.to_vec();
+    }
+
+    let at = |i: isize| {
+        if closed {
+            points[i.rem_euclid(count as isize) as usize]
+        } else {
+            points[i.clamp(0, count as isize - 1) as usize]
+        }
+    };
+
+    let segments = if closed { count } else { count - 1 };
+    let mut curve = Vec::with_capacity(segments * steps + 1);
+
+    for segment in 0..segments as isize {
+        for step in 0..steps {
+            let t = step as f32 / steps as f32;
+            curve.push(catmull_rom(
+                at(segment - 1),
+                at(segment),
+                at(segment + 1),
+                at(segment + 2),
+                t,
+            ));
+        }
+    }
+    curve.push(at(segments as isize));
+
+    curve
 }
 
 // pub fn random_point(width: i32, height: i32) -> Vector2{